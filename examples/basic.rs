@@ -33,7 +33,7 @@ async fn main() {
     let client = Client::create(code, dob, None).await;
 
     match client {
-        Ok(mut client) => {
+        Ok(client) => {
             // grabbing the student info
             let student = client.get_student_info().await.unwrap();
             
@@ -1,15 +1,19 @@
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
-use chrono::NaiveDate;
-use serde::Deserialize;
+use crate::api::dates::{
+    deserialize_datetime, deserialize_optional_date, deserialize_optional_time,
+};
+use crate::client::{ErrorResponse, SuccessResponse, Client};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ActivityStyle {
     pub border_color: Option<String>,
     pub custom_class: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ActivityPoint {
     pub id: usize,
     #[serde(rename = "type")]
@@ -17,7 +21,8 @@ pub struct ActivityPoint {
     pub polarity: String,
     pub reason: String,
     pub score: isize,
-    pub timestamp: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub timestamp: NaiveDateTime,
     pub timestamp_custom_time: Option<String>,
     pub style: ActivityStyle,
     pub pupil_name: String,
@@ -27,15 +32,18 @@ pub struct ActivityPoint {
     pub note: Option<String>,
     pub _can_delete: bool,
     pub badges: Option<String>,
-    pub detention_date: Option<String>,
-    pub detention_time: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
+    pub detention_date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub detention_time: Option<NaiveTime>,
     pub detention_location: Option<String>,
     pub detention_type: Option<String>,
 }
 
 pub type ActivityData = Vec<ActivityPoint>;
 
-#[derive(Debug)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
 pub enum LastId {
     Boolean(bool),
     Number(usize),
@@ -60,10 +68,12 @@ impl<'de> Deserialize<'de> for LastId {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ActivityMeta {
-    pub start_date: String,
-    pub end_date: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub start_date: NaiveDateTime,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub end_date: NaiveDateTime,
     pub step_size: String,
     pub last_id: Option<LastId>,
     pub detention_alias_uc: String,
@@ -99,7 +109,7 @@ impl Client {
     /// ));
     /// ```
     pub async fn get_activity(
-        &mut self,
+        &self,
         options: Option<ActivityOptions>,
     ) -> Result<Activity, ErrorResponse> {
         let mut params = url::form_urlencoded::Serializer::new(String::new());
@@ -118,13 +128,9 @@ impl Client {
 
         let params = params.finish();
 
-        let request = self
-            .build_get(format!("/activity/{}?{}", self.student_id, params))
-            .await?
-            .send()
+        let text = self
+            .request(Method::GET, format!("/activity/{}?{}", self.student_id, params), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Activity = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -145,7 +151,7 @@ impl Client {
     /// );
     /// ```
     pub async fn get_full_activity(
-        &mut self,
+        &self,
         options: FullActivityOptions,
     ) -> Result<ActivityData, ErrorResponse> {
         let mut data: ActivityData = vec![];
@@ -203,7 +209,7 @@ mod tests {
             })
             .await;
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_activity(None).await.unwrap();
 
@@ -259,7 +265,7 @@ mod tests {
             })
             .await;
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_activity(None).await.unwrap();
 
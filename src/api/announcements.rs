@@ -1,19 +1,24 @@
 use crate::{
-    api::helpers::deserialize_yes_no_bool,
-    client::{ErrorResponse, CCParser, SuccessResponse, Client},
+    api::{
+        dates::deserialize_datetime,
+        helpers::{deserialize_yes_no_bool, serialize_yes_no_bool},
+    },
+    client::{ErrorResponse, SuccessResponse, Client},
 };
-use serde::Deserialize;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::helpers::Empty;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Attachment {
     pub filename: String,
     pub url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Announcement {
     pub id: usize,
     pub title: String,
@@ -21,23 +26,42 @@ pub struct Announcement {
     pub school_name: String,
     pub teacher_name: String,
     pub school_logo: Option<String>,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub sticky: bool,
     pub state: Option<String>,
-    pub timestamp: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub timestamp: NaiveDateTime,
     pub attachments: Vec<Attachment>,
     pub for_pupils: Vec<Value>,
     pub comment_visibility: String,
 
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub allow_comments: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub allow_reactions: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub allow_consent: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub priority_pinned: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub requires_consent: bool,
 
     pub can_change_consent: bool,
@@ -52,14 +76,10 @@ pub type Announcements = SuccessResponse<AnnouncementsData, AnnouncementsMeta>;
 
 impl Client {
     /// Gets the current student's announcements 
-    pub async fn get_announcements(&mut self) -> Result<Announcements, ErrorResponse> {
-        let request = self
-            .build_get(format!("/announcements/{}", self.student_id))
-            .await?
-            .send()
+    pub async fn get_announcements(&self) -> Result<Announcements, ErrorResponse> {
+        let text = self
+            .request(Method::GET, format!("/announcements/{}", self.student_id), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Announcements = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -113,7 +133,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_announcements().await.unwrap();
 
@@ -0,0 +1,460 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use reqwest::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+    StatusCode,
+};
+
+use crate::{
+    api::{
+        announcements::{Announcement, Attachment},
+        homework::{Homework, HomeworkAttachment, ValidatedHomeworkAttachment},
+    },
+    client::{Client, ErrorResponse},
+};
+
+/// A file which can be downloaded with `Client::download_attachment`.
+pub trait DownloadableAttachment {
+    /// The name of the file, as it was uploaded.
+    fn file_name(&self) -> &str;
+    /// The url of the file, which may be relative to the ClassCharts `base_url`.
+    fn url(&self) -> &str;
+}
+
+impl DownloadableAttachment for ValidatedHomeworkAttachment {
+    fn file_name(&self) -> &str {
+        return &self.file_name;
+    }
+
+    fn url(&self) -> &str {
+        return &self.validated_file;
+    }
+}
+
+impl DownloadableAttachment for HomeworkAttachment {
+    fn file_name(&self) -> &str {
+        return &self.file_name;
+    }
+
+    fn url(&self) -> &str {
+        return &self.validated_file;
+    }
+}
+
+impl DownloadableAttachment for Attachment {
+    fn file_name(&self) -> &str {
+        return &self.filename;
+    }
+
+    fn url(&self) -> &str {
+        return &self.url;
+    }
+}
+
+/// Something with attachments, which can all be saved with `Client::save_attachments`.
+pub trait HasAttachments {
+    fn attachments(&self) -> Vec<&dyn DownloadableAttachment>;
+}
+
+impl HasAttachments for Homework {
+    fn attachments(&self) -> Vec<&dyn DownloadableAttachment> {
+        return self
+            .validated_attachments
+            .iter()
+            .map(|attachment| attachment as &dyn DownloadableAttachment)
+            .collect();
+    }
+}
+
+impl HasAttachments for Announcement {
+    fn attachments(&self) -> Vec<&dyn DownloadableAttachment> {
+        return self
+            .attachments
+            .iter()
+            .map(|attachment| attachment as &dyn DownloadableAttachment)
+            .collect();
+    }
+}
+
+/// How many redirects `download_attachment` follows, e.g. to the storage a file is hosted on.
+const MAX_REDIRECTS: usize = 5;
+
+/// A downloaded attachment. The whole file is read into memory before it is returned, as a
+/// `Transport` always returns the complete response body, so downloads aren't streamed.
+#[derive(Debug, Clone)]
+pub struct AttachmentDownload {
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+impl Client {
+    /// Downloads a homework or announcement attachment.
+    /// The session is sent with the request if the file is hosted by ClassCharts. Redirects are
+    /// followed, but the session isn't sent again once a redirect leads to another origin.
+    ///
+    /// The whole file is buffered in memory, so this isn't suited to very large files.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let homework = &client.get_homeworks(None).await?.data[0];
+    /// let file = client.download_attachment(&homework.validated_attachments[0]).await?;
+    ///
+    /// println!("{} is {} bytes", file.file_name, file.data.len());
+    /// ```
+    pub async fn download_attachment<A>(
+        &self,
+        attachment: &A,
+    ) -> Result<AttachmentDownload, ErrorResponse>
+    where
+        A: DownloadableAttachment + ?Sized,
+    {
+        let mut url = self.resolve_file_url(attachment.url())?;
+        let mut send_session = true;
+        let mut redirects = 0;
+
+        let response = loop {
+            let response = self.build_file_get(&url, send_session).await?.send().await?;
+
+            let is_redirect = matches!(
+                response.status,
+                StatusCode::MOVED_PERMANENTLY
+                    | StatusCode::FOUND
+                    | StatusCode::SEE_OTHER
+                    | StatusCode::TEMPORARY_REDIRECT
+                    | StatusCode::PERMANENT_REDIRECT
+            );
+
+            if !is_redirect || redirects == MAX_REDIRECTS {
+                break response;
+            }
+
+            let Some(location) = response
+                .headers
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+            else {
+                break response;
+            };
+
+            let next = url
+                .join(location)
+                .map_err(|err| ErrorResponse::InvalidRequestError(err.to_string()))?;
+
+            send_session = send_session && next.origin() == url.origin();
+            url = next;
+            redirects += 1;
+        };
+
+        if !response.status.is_success() {
+            return Err(ErrorResponse::HttpStatusError(response.status));
+        }
+
+        let content_type = response
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        let content_length = response
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+
+        return Ok(AttachmentDownload {
+            file_name: attachment.file_name().to_string(),
+            content_type,
+            content_length,
+            data: response.body,
+        });
+    }
+
+    /// Downloads every attachment of a `Homework` or `Announcement` into `directory`, which is
+    /// created if it doesn't exist. File names are sanitized, and duplicate names are numbered.
+    /// Files already in `directory` are never overwritten, so a new file is numbered instead.
+    /// Returns the paths the attachments were saved to.
+    ///
+    /// Each attachment is downloaded into memory with `download_attachment` before it is
+    /// written.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let homework = &client.get_homeworks(None).await?.data[0];
+    /// client.save_attachments(homework, "homework").await?;
+    /// ```
+    pub async fn save_attachments<T, P>(
+        &self,
+        item: &T,
+        directory: P,
+    ) -> Result<Vec<PathBuf>, ErrorResponse>
+    where
+        T: HasAttachments + ?Sized,
+        P: AsRef<Path>,
+    {
+        let directory = directory.as_ref();
+        tokio::fs::create_dir_all(directory).await?;
+
+        let mut used_names = HashSet::new();
+        let mut entries = tokio::fs::read_dir(directory).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            used_names.insert(entry.file_name().to_string_lossy().to_lowercase());
+        }
+
+        let mut paths = vec![];
+
+        for attachment in item.attachments() {
+            let download = self.download_attachment(attachment).await?;
+
+            let file_name = sanitize_file_name(attachment.file_name());
+            let path = directory.join(unique_file_name(&file_name, &mut used_names));
+
+            tokio::fs::write(&path, &download.data).await?;
+            paths.push(path);
+        }
+
+        return Ok(paths);
+    }
+}
+
+/// Makes a file name safe to write on any platform, so an attachment can't escape the directory
+/// it is saved into.
+fn sanitize_file_name(file_name: &str) -> String {
+    let sanitized: String = file_name
+        .chars()
+        .map(|char| match char {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            char if char.is_control() => '_',
+            char => char,
+        })
+        .collect();
+
+    let sanitized = sanitized.trim_matches(|char: char| char == '.' || char.is_whitespace());
+
+    if sanitized.is_empty() {
+        return "attachment".to_string();
+    }
+
+    if is_reserved_file_name(sanitized) {
+        return format!("_{}", sanitized);
+    }
+
+    return sanitized.to_string();
+}
+
+/// Whether Windows reserves the name for a device, e.g. `CON` or `nul.txt`. The extension
+/// doesn't matter, so only the part before the first `.` is checked.
+fn is_reserved_file_name(file_name: &str) -> bool {
+    let stem = file_name.split('.').next().unwrap_or(file_name).trim_end();
+    let stem = stem.to_ascii_uppercase();
+
+    if matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL") {
+        return true;
+    }
+
+    let port = stem.strip_prefix("COM").or_else(|| stem.strip_prefix("LPT"));
+
+    return matches!(port, Some("1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9"));
+}
+
+/// Numbers a file name, e.g. `file (1).pdf`, if it has already been used.
+fn unique_file_name(file_name: &str, used_names: &mut HashSet<String>) -> String {
+    let mut candidate = file_name.to_string();
+    let mut count = 1;
+
+    while used_names.contains(&candidate.to_lowercase()) {
+        candidate = match file_name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{} ({}).{}", stem, count, extension),
+            None => format!("{} ({})", file_name, count),
+        };
+        count += 1;
+    }
+
+    used_names.insert(candidate.to_lowercase());
+
+    return candidate;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn sanitize_file_name_test() {
+        assert_eq!(sanitize_file_name("essay.pdf"), "essay.pdf");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(sanitize_file_name("what? <why>.docx"), "what_ _why_.docx");
+        assert_eq!(sanitize_file_name(" .. "), "attachment");
+        assert_eq!(sanitize_file_name("CON"), "_CON");
+        assert_eq!(sanitize_file_name("nul.txt"), "_nul.txt");
+        assert_eq!(sanitize_file_name("Com1 .tar.gz"), "_Com1 .tar.gz");
+        assert_eq!(sanitize_file_name("lpt9"), "_lpt9");
+        assert_eq!(sanitize_file_name("com0.pdf"), "com0.pdf");
+        assert_eq!(sanitize_file_name("console.log"), "console.log");
+
+        let mut used_names = HashSet::new();
+        assert_eq!(unique_file_name("a.pdf", &mut used_names), "a.pdf");
+        assert_eq!(unique_file_name("A.pdf", &mut used_names), "A (1).pdf");
+        assert_eq!(unique_file_name("a.pdf", &mut used_names), "a (2).pdf");
+    }
+
+    #[tokio::test]
+    async fn download_attachment_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+        let other_server = MockServer::start();
+
+        // Create a mock on the server.
+        let attachment_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/uploads/essay.pdf")
+                .header("Authorization", "Basic session_id");
+            then.status(200)
+                .header("content-type", "application/pdf")
+                .body("essay contents");
+        });
+
+        let external_response = other_server.mock(|when, then| {
+            when.method(GET)
+                .path("/essay.pdf")
+                .matches(|request| {
+                    !request.headers.iter().flatten().any(|(name, _)| {
+                        name.eq_ignore_ascii_case("authorization")
+                    })
+                });
+            then.status(200).body("external contents");
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let download = client
+            .download_attachment(&Attachment {
+                filename: "essay.pdf".to_string(),
+                url: "/uploads/essay.pdf".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(download.content_type.as_deref(), Some("application/pdf"));
+        assert_eq!(download.content_length, Some(14));
+        assert_eq!(download.data, b"essay contents");
+
+        let external = client
+            .download_attachment(&Attachment {
+                filename: "essay.pdf".to_string(),
+                url: other_server.url("/essay.pdf"),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(external.data, b"external contents");
+
+        attachment_response.assert();
+        external_response.assert();
+    }
+
+    #[tokio::test]
+    async fn download_attachment_redirect_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+        let storage_server = MockServer::start();
+
+        // Create a mock on the server.
+        let attachment_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/uploads/essay.pdf")
+                .header("Authorization", "Basic session_id");
+            then.status(302).header("location", "/files/essay.pdf");
+        });
+
+        let file_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/essay.pdf")
+                .header("Authorization", "Basic session_id");
+            then.status(302)
+                .header("location", storage_server.url("/essay.pdf?signature=abc"));
+        });
+
+        let storage_response = storage_server.mock(|when, then| {
+            when.method(GET)
+                .path("/essay.pdf")
+                .query_param("signature", "abc")
+                .matches(|request| {
+                    !request.headers.iter().flatten().any(|(name, _)| {
+                        name.eq_ignore_ascii_case("authorization")
+                            || name.eq_ignore_ascii_case("cookie")
+                    })
+                });
+            then.status(200).body("essay contents");
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let download = client
+            .download_attachment(&Attachment {
+                filename: "essay.pdf".to_string(),
+                url: "/uploads/essay.pdf".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(download.data, b"essay contents");
+
+        attachment_response.assert();
+        file_response.assert();
+        storage_response.assert();
+    }
+
+    struct Attachments(Vec<Attachment>);
+
+    impl HasAttachments for Attachments {
+        fn attachments(&self) -> Vec<&dyn DownloadableAttachment> {
+            return self
+                .0
+                .iter()
+                .map(|attachment| attachment as &dyn DownloadableAttachment)
+                .collect();
+        }
+    }
+
+    #[tokio::test]
+    async fn save_attachments_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let attachment_response = server.mock(|when, then| {
+            when.method(GET).path("/uploads/essay.pdf");
+            then.status(200).body("new essay");
+        });
+
+        let directory = std::env::temp_dir().join(format!("classcharts-{}", server.port()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("Essay.pdf"), "old essay").unwrap();
+
+        let client = Client::generate_mock(server.base_url());
+        let attachment = Attachment {
+            filename: "essay.pdf".to_string(),
+            url: "/uploads/essay.pdf".to_string(),
+        };
+
+        let paths = client
+            .save_attachments(&Attachments(vec![attachment.clone(), attachment]), &directory)
+            .await
+            .unwrap();
+
+        assert_eq!(paths, vec![directory.join("essay (1).pdf"), directory.join("essay (2).pdf")]);
+        assert_eq!(std::fs::read_to_string(directory.join("Essay.pdf")).unwrap(), "old essay");
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "new essay");
+
+        std::fs::remove_dir_all(&directory).unwrap();
+        attachment_response.assert_hits(2);
+    }
+}
@@ -1,27 +1,228 @@
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
-use std::collections::HashMap;
+use crate::api::dates::{deserialize_dates, deserialize_datetime};
+use crate::api::helpers::WireNumber;
+use crate::api::lessons::Lesson;
+use crate::client::{ErrorResponse, SuccessResponse, Client};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 
-use chrono::NaiveDate;
-use serde::Deserialize;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use reqwest::Method;
 
 pub struct AttendanceOptions {
     pub from: NaiveDate,
     pub to: NaiveDate,
 }
 
-#[derive(Deserialize, Debug)]
+/// The status ClassCharts gives a session, alongside its register code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AttendancePeriodStatus {
-    #[serde(rename = "present")]
     Present,
-    #[serde(rename = "ignore")]
+    Late,
+    Absent,
+    /// The session isn't counted, e.g. a weekend or before the student joined.
     Ignore,
+    /// Any other status, as ClassCharts returned it.
+    Other(String),
 }
 
-#[derive(Debug)]
-pub enum LateMinutes {
-    String(String),
-    Number(usize),
+impl AttendancePeriodStatus {
+    pub fn as_str(&self) -> &str {
+        return match self {
+            AttendancePeriodStatus::Present => "present",
+            AttendancePeriodStatus::Late => "late",
+            AttendancePeriodStatus::Absent => "absent",
+            AttendancePeriodStatus::Ignore => "ignore",
+            AttendancePeriodStatus::Other(status) => status,
+        };
+    }
+}
+
+impl From<&str> for AttendancePeriodStatus {
+    fn from(status: &str) -> Self {
+        return match status {
+            "present" => AttendancePeriodStatus::Present,
+            "late" => AttendancePeriodStatus::Late,
+            "absent" => AttendancePeriodStatus::Absent,
+            "ignore" => AttendancePeriodStatus::Ignore,
+            status => AttendancePeriodStatus::Other(status.to_string()),
+        };
+    }
+}
+
+impl Serialize for AttendancePeriodStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        return serializer.serialize_str(self.as_str());
+    }
+}
+
+impl<'de> Deserialize<'de> for AttendancePeriodStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let status = String::deserialize(deserializer)?;
+
+        return Ok(AttendancePeriodStatus::from(status.as_str()));
+    }
+}
+
+/// A DfE attendance register code, grouped by what it means. Every variant keeps the code as
+/// ClassCharts returned it, e.g. `AuthorisedAbsence("I")` for illness.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttendanceCode {
+    /// `/` or `\`, present in the morning or afternoon.
+    Present(String),
+    /// `L`, late before the register closed, which counts as present.
+    Late(String),
+    /// E.g. `I` (illness), `M` (medical appointment), `H` (authorised holiday), `E` (excluded).
+    AuthorisedAbsence(String),
+    /// `G` (unauthorised holiday), `N` (no reason yet), `O` (other) or `U` (late after the
+    /// register closed).
+    UnauthorisedAbsence(String),
+    /// Attending an approved educational activity away from school, which counts as present,
+    /// e.g. `V` (educational visit), `P` (sporting activity) or `W` (work experience).
+    ApprovedEducationalActivity(String),
+    /// The session isn't counted, e.g. `X` (not required to attend), `D` (dual registered, so
+    /// attending another school), `Y` (unable to attend due to exceptional circumstances), `Z`
+    /// (not on roll) or `#` (school closed).
+    NotRequired(String),
+    /// No code, or a code this crate doesn't know.
+    Unknown(String),
+}
+
+impl AttendanceCode {
+    /// The code as ClassCharts returned it.
+    pub fn as_str(&self) -> &str {
+        return match self {
+            AttendanceCode::Present(code)
+            | AttendanceCode::Late(code)
+            | AttendanceCode::AuthorisedAbsence(code)
+            | AttendanceCode::UnauthorisedAbsence(code)
+            | AttendanceCode::ApprovedEducationalActivity(code)
+            | AttendanceCode::NotRequired(code)
+            | AttendanceCode::Unknown(code) => code,
+        };
+    }
+
+    /// Whether the student attended, which includes being late and approved activities.
+    pub fn is_present(&self) -> bool {
+        return matches!(
+            self,
+            AttendanceCode::Present(_)
+                | AttendanceCode::Late(_)
+                | AttendanceCode::ApprovedEducationalActivity(_)
+        );
+    }
+
+    /// Whether the student was absent, authorised or not.
+    pub fn is_absent(&self) -> bool {
+        return matches!(
+            self,
+            AttendanceCode::AuthorisedAbsence(_) | AttendanceCode::UnauthorisedAbsence(_)
+        );
+    }
+
+    /// Whether the session counts towards the student's attendance percentage.
+    pub fn is_counted(&self) -> bool {
+        return self.is_present() || self.is_absent();
+    }
+}
+
+impl From<&str> for AttendanceCode {
+    fn from(code: &str) -> Self {
+        let owned = code.to_string();
+
+        // some codes have a numbered variant, e.g. `C1` or `Y2`
+        let letter = code.trim().chars().next();
+
+        return match letter {
+            Some('/' | '\\') => AttendanceCode::Present(owned),
+            Some('L') => AttendanceCode::Late(owned),
+            Some('C' | 'E' | 'H' | 'I' | 'M' | 'R' | 'S' | 'T') => {
+                AttendanceCode::AuthorisedAbsence(owned)
+            }
+            // `J` (interview) was an approved educational activity, but was replaced by the
+            // authorised absence `J1`
+            Some('J') => match code.trim() {
+                "J" => AttendanceCode::ApprovedEducationalActivity(owned),
+                _ => AttendanceCode::AuthorisedAbsence(owned),
+            },
+            Some('G' | 'N' | 'O' | 'U') => AttendanceCode::UnauthorisedAbsence(owned),
+            Some('B' | 'K' | 'P' | 'V' | 'W') => AttendanceCode::ApprovedEducationalActivity(owned),
+            Some('D' | 'X' | 'Y' | 'Z' | 'Q' | '#') => AttendanceCode::NotRequired(owned),
+            _ => AttendanceCode::Unknown(owned),
+        };
+    }
+}
+
+impl Serialize for AttendanceCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        return serializer.serialize_str(self.as_str());
+    }
+}
+
+impl<'de> Deserialize<'de> for AttendanceCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let code = String::deserialize(deserializer)?;
+
+        return Ok(AttendanceCode::from(code.as_str()));
+    }
+}
+
+/// How many minutes late the student was to a session. ClassCharts sends this as a number, a
+/// string, or an empty string when it wasn't recorded, and it is serialized the same way.
+#[derive(Debug, Clone, Default)]
+pub struct LateMinutes {
+    minutes: Option<u32>,
+    wire: WireNumber,
+}
+
+impl LateMinutes {
+    pub fn new(minutes: Option<u32>) -> LateMinutes {
+        return LateMinutes {
+            minutes,
+            wire: WireNumber::Number,
+        };
+    }
+
+    /// The minutes late, or `None` if ClassCharts didn't record any.
+    pub fn minutes(&self) -> Option<u32> {
+        return self.minutes;
+    }
+}
+
+impl PartialEq for LateMinutes {
+    fn eq(&self, other: &Self) -> bool {
+        return self.minutes == other.minutes;
+    }
+}
+
+impl Eq for LateMinutes {}
+
+impl From<LateMinutes> for Option<u32> {
+    fn from(late_minutes: LateMinutes) -> Self {
+        return late_minutes.minutes;
+    }
+}
+
+impl Serialize for LateMinutes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        return self.wire.serialize(self.minutes, serializer);
+    }
 }
 
 impl<'de> Deserialize<'de> for LateMinutes {
@@ -30,41 +231,377 @@ impl<'de> Deserialize<'de> for LateMinutes {
         D: serde::Deserializer<'de>,
     {
         let value: Value = Deserialize::deserialize(deserializer)?;
+        let (minutes, wire) =
+            WireNumber::parse(value, "late_minutes").map_err(serde::de::Error::custom)?;
 
-        match value {
-            Value::String(s) => Ok(LateMinutes::String(s)),
-            Value::Number(num) if num.is_u64() => {
-                Ok(LateMinutes::Number(num.as_u64().unwrap() as usize))
-            }
-            _ => Err(serde::de::Error::custom(
-                "Invalid format for 'late_minutes' field",
-            )),
-        }
+        return Ok(LateMinutes { minutes, wire });
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AttendancePeriod {
-    pub code: String,
+    pub code: AttendanceCode,
     pub status: AttendancePeriodStatus,
     pub late_minutes: LateMinutes,
     pub lesson_name: Option<String>,
     pub room_name: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AttendanceMeta {
-    pub dates: Vec<String>,
+    #[serde(deserialize_with = "deserialize_dates")]
+    pub dates: Vec<NaiveDate>,
     pub sessions: Vec<String>,
-    pub start_date: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub start_date: NaiveDateTime,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub end_date: NaiveDateTime,
     pub percentage: String,
     pub percentage_singe_august: String,
 }
 
-pub type AttendanceData = HashMap<String, HashMap<String, AttendancePeriod>>;
+pub type AttendanceData = HashMap<NaiveDate, HashMap<String, AttendancePeriod>>;
 
 pub type Attendance = SuccessResponse<AttendanceData, AttendanceMeta>;
 
+/// A single session's mark in an `AttendanceRecord`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttendanceSession {
+    /// The name of the session, e.g. `AM` or `Period 1`.
+    pub name: String,
+    pub period: AttendancePeriod,
+}
+
+/// A day's marks in an `AttendanceRecord`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttendanceDay {
+    pub date: NaiveDate,
+    /// The marked sessions, in the order of `AttendanceMeta.sessions`.
+    pub sessions: Vec<AttendanceSession>,
+}
+
+impl AttendanceDay {
+    /// The mark for a session, e.g. `AM`.
+    pub fn session(&self, name: &str) -> Option<&AttendancePeriod> {
+        return self
+            .sessions
+            .iter()
+            .find(|session| session.name == name)
+            .map(|session| &session.period);
+    }
+}
+
+/// The attendance from `get_attendance`, ordered by date and session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AttendanceRecord {
+    /// Every session name, from `AttendanceMeta.sessions`, followed by any sessions ClassCharts
+    /// returned marks for but didn't list.
+    pub sessions: Vec<String>,
+    pub days: BTreeMap<NaiveDate, AttendanceDay>,
+}
+
+impl AttendanceRecord {
+    pub fn new(attendance: &Attendance) -> AttendanceRecord {
+        let mut sessions = attendance.meta.sessions.clone();
+
+        let mut unlisted: Vec<&String> = attendance
+            .data
+            .values()
+            .flat_map(|periods| periods.keys())
+            .filter(|name| !sessions.contains(name))
+            .collect();
+        unlisted.sort();
+        unlisted.dedup();
+        sessions.extend(unlisted.into_iter().cloned());
+
+        let days = attendance
+            .data
+            .iter()
+            .map(|(date, periods)| {
+                let sessions = sessions
+                    .iter()
+                    .filter_map(|name| {
+                        return periods.get(name).map(|period| AttendanceSession {
+                            name: name.clone(),
+                            period: period.clone(),
+                        });
+                    })
+                    .collect();
+
+                return (*date, AttendanceDay { date: *date, sessions });
+            })
+            .collect();
+
+        return AttendanceRecord { sessions, days };
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Option<&AttendanceDay> {
+        return self.days.get(&date);
+    }
+
+    /// The mark for a session on a date.
+    pub fn get(&self, date: NaiveDate, session: &str) -> Option<&AttendancePeriod> {
+        return self.day(date)?.session(session);
+    }
+
+    /// Every marked session, ordered by date and session.
+    pub fn iter(&self) -> impl Iterator<Item = (NaiveDate, &AttendanceSession)> {
+        return self
+            .days
+            .values()
+            .flat_map(|day| day.sessions.iter().map(move |session| (day.date, session)));
+    }
+}
+
+impl From<&Attendance> for AttendanceRecord {
+    fn from(attendance: &Attendance) -> AttendanceRecord {
+        return AttendanceRecord::new(attendance);
+    }
+}
+
+/// Attendance below this percentage is persistent absence, as defined by the DfE.
+pub const PERSISTENT_ABSENCE_THRESHOLD: f64 = 90.0;
+
+/// A term of the school year. Terms are split by month: autumn is September to December, spring
+/// is January to March, and summer is April to August.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Term {
+    Autumn,
+    Spring,
+    Summer,
+}
+
+impl Term {
+    pub fn as_str(&self) -> &str {
+        return match self {
+            Term::Autumn => "autumn",
+            Term::Spring => "spring",
+            Term::Summer => "summer",
+        };
+    }
+}
+
+/// A term in a particular school year. It is serialized as a string like `2023-autumn`, so it
+/// can be used as a JSON object key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchoolTerm {
+    /// The calendar year the school year started in, e.g. `2023` for 2023/24.
+    pub school_year: i32,
+    pub term: Term,
+}
+
+impl SchoolTerm {
+    /// The term a date is in.
+    pub fn of(date: NaiveDate) -> SchoolTerm {
+        let (school_year, term) = match date.month() {
+            9..=12 => (date.year(), Term::Autumn),
+            1..=3 => (date.year() - 1, Term::Spring),
+            _ => (date.year() - 1, Term::Summer),
+        };
+
+        return SchoolTerm { school_year, term };
+    }
+
+    fn parse(term: &str) -> Option<SchoolTerm> {
+        let (school_year, term) = term.rsplit_once('-')?;
+
+        let term = match term {
+            "autumn" => Term::Autumn,
+            "spring" => Term::Spring,
+            "summer" => Term::Summer,
+            _ => return None,
+        };
+
+        return Some(SchoolTerm { school_year: school_year.parse().ok()?, term });
+    }
+}
+
+impl fmt::Display for SchoolTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}-{}", self.school_year, self.term.as_str());
+    }
+}
+
+impl Serialize for SchoolTerm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        return serializer.collect_str(self);
+    }
+}
+
+impl<'de> Deserialize<'de> for SchoolTerm {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let term = String::deserialize(deserializer)?;
+
+        return SchoolTerm::parse(&term).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid school term: {term}"))
+        });
+    }
+}
+
+/// Counts of marked sessions. Sessions which aren't counted towards attendance, like `X` or `#`,
+/// are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AttendanceCounts {
+    /// Sessions the student attended, including late marks and approved educational activities.
+    pub present: u32,
+    /// Sessions the student was late to, before the register closed.
+    pub late: u32,
+    pub authorised_absences: u32,
+    pub unauthorised_absences: u32,
+    /// The total minutes the student was late.
+    pub late_minutes: u32,
+}
+
+impl AttendanceCounts {
+    fn add(&mut self, period: &AttendancePeriod) {
+        match &period.code {
+            AttendanceCode::AuthorisedAbsence(_) => self.authorised_absences += 1,
+            AttendanceCode::UnauthorisedAbsence(_) => self.unauthorised_absences += 1,
+            code if code.is_present() => self.present += 1,
+            _ => return,
+        }
+
+        if matches!(period.code, AttendanceCode::Late(_)) {
+            self.late += 1;
+        }
+
+        self.late_minutes += period.late_minutes.minutes().unwrap_or(0);
+    }
+
+    /// The number of sessions the student could have attended.
+    pub fn possible(&self) -> u32 {
+        return self.present + self.authorised_absences + self.unauthorised_absences;
+    }
+
+    pub fn absences(&self) -> u32 {
+        return self.authorised_absences + self.unauthorised_absences;
+    }
+
+    /// The percentage of possible sessions the student attended, or `None` if there were none.
+    pub fn percentage(&self) -> Option<f64> {
+        if self.possible() == 0 {
+            return None;
+        }
+
+        return Some(f64::from(self.present) / f64::from(self.possible()) * 100.0);
+    }
+
+    /// Whether the attendance is below the `PERSISTENT_ABSENCE_THRESHOLD`.
+    pub fn is_persistent_absence(&self) -> bool {
+        return self
+            .percentage()
+            .is_some_and(|percentage| percentage < PERSISTENT_ABSENCE_THRESHOLD);
+    }
+}
+
+/// Attendance statistics calculated from `get_attendance`, rather than the `percentage`
+/// ClassCharts returns.
+///
+/// `overall`, `by_weekday` and `by_term` count the `AM` and `PM` registration sessions (or every
+/// session, if there are none), and `by_subject` counts the lesson sessions, which are every
+/// session that isn't `AM` or `PM`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AttendanceStats {
+    pub overall: AttendanceCounts,
+    /// Keyed by the session's `lesson_name`, or the `subject_name` of its lesson.
+    pub by_subject: BTreeMap<String, AttendanceCounts>,
+    pub by_weekday: HashMap<Weekday, AttendanceCounts>,
+    pub by_term: BTreeMap<SchoolTerm, AttendanceCounts>,
+}
+
+impl AttendanceStats {
+    pub fn new(attendance: &Attendance) -> AttendanceStats {
+        return AttendanceStats::with_lessons(attendance, []);
+    }
+
+    /// Calculates the statistics, using the lessons (e.g. from `get_timetable`) to name the
+    /// subject of lesson sessions without a `lesson_name`.
+    pub fn with_lessons<'a, I>(attendance: &Attendance, lessons: I) -> AttendanceStats
+    where
+        I: IntoIterator<Item = &'a Lesson>,
+    {
+        let subjects: HashMap<(NaiveDate, String), &str> = lessons
+            .into_iter()
+            .map(|lesson| {
+                let key = (lesson.date, normalize_period(&lesson.period_number));
+                return (key, lesson.subject_name.as_str());
+            })
+            .collect();
+
+        let record = AttendanceRecord::new(attendance);
+        let has_registration = record
+            .iter()
+            .any(|(_, session)| is_registration(&session.name));
+
+        let mut stats = AttendanceStats::default();
+
+        for (date, session) in record.iter() {
+            if !has_registration || is_registration(&session.name) {
+                stats.overall.add(&session.period);
+                stats.by_weekday.entry(date.weekday()).or_default().add(&session.period);
+                stats.by_term.entry(SchoolTerm::of(date)).or_default().add(&session.period);
+            }
+
+            if is_registration(&session.name) {
+                continue;
+            }
+
+            let subject = session
+                .period
+                .lesson_name
+                .as_deref()
+                .filter(|name| !name.is_empty())
+                .or_else(|| subjects.get(&(date, normalize_period(&session.name))).copied());
+
+            if let Some(subject) = subject {
+                stats
+                    .by_subject
+                    .entry(subject.to_string())
+                    .or_default()
+                    .add(&session.period);
+            }
+        }
+
+        return stats;
+    }
+
+    /// Whether the student's overall attendance is below the `PERSISTENT_ABSENCE_THRESHOLD`.
+    pub fn is_persistent_absence(&self) -> bool {
+        return self.overall.is_persistent_absence();
+    }
+
+    /// The subjects with attendance below the `PERSISTENT_ABSENCE_THRESHOLD`.
+    pub fn persistent_absence_subjects(&self) -> Vec<&str> {
+        return self
+            .by_subject
+            .iter()
+            .filter(|(_, counts)| counts.is_persistent_absence())
+            .map(|(subject, _)| subject.as_str())
+            .collect();
+    }
+}
+
+fn is_registration(session: &str) -> bool {
+    return session == "AM" || session == "PM";
+}
+
+/// Matches attendance session names to lesson periods, e.g. `Period 1` to `P1`.
+fn normalize_period(period: &str) -> String {
+    let period = period.trim().to_lowercase();
+    let period = period.strip_prefix("period").unwrap_or(&period).trim();
+
+    return match period.strip_prefix('p') {
+        Some(number) if number.starts_with(|char: char| char.is_ascii_digit()) => number.to_string(),
+        _ => period.to_string(),
+    };
+}
+
 impl Client {
     /// Gets the current student's attendance 
     /// This is using `chrono` for parsing the date.
@@ -80,7 +617,7 @@ impl Client {
     /// ));
     /// ```
     pub async fn get_attendance(
-        &mut self,
+        &self,
         options: Option<AttendanceOptions>,
     ) -> Result<Attendance, ErrorResponse> {
         let mut params = url::form_urlencoded::Serializer::new(String::new());
@@ -92,13 +629,9 @@ impl Client {
 
         let params = params.finish();
 
-        let request = self
-            .build_get(format!("/attendance/{}?{}", self.student_id, params))
-            .await?
-            .send()
+        let text = self
+            .request(Method::GET, format!("/attendance/{}?{}", self.student_id, params), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Attendance = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -190,10 +723,221 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
-        let _ = client.get_attendance(None).await.unwrap();
+        let attendance = client.get_attendance(None).await.unwrap();
+        let record = AttendanceRecord::from(&attendance);
+        let date = NaiveDate::from_ymd_opt(2023, 8, 25).unwrap();
+
+        assert_eq!(record.sessions[0], "AM");
+        assert_eq!(record.day(date).unwrap().sessions[2].name, "Period 1");
+        assert_eq!(record.day(date).unwrap().sessions.len(), 8);
+        assert_eq!(record.get(date, "AM").unwrap().code, AttendanceCode::NotRequired("#".to_string()));
+        assert_eq!(record.get(date, "PM").unwrap().status, AttendancePeriodStatus::Ignore);
+        assert!(record.get(date, "Period 6").is_none());
+        assert_eq!(record.iter().count(), 8);
 
         attendance_response.assert();
     }
+
+    #[test]
+    fn attendance_stats_test() {
+        let period = |code: &str, late_minutes: u32, lesson_name: Option<&str>| {
+            return json!({
+                "code": code,
+                "status": "present",
+                "late_minutes": late_minutes,
+                "lesson_name": lesson_name
+            });
+        };
+
+        let attendance: Attendance = serde_json::from_value(json!({
+            "success": 1,
+            "data": {
+                // a monday
+                "2023-09-25": {
+                    "AM": period("/", 0, None),
+                    "PM": period("L", 5, None),
+                    "Period 1": period("/", 0, Some("Maths")),
+                    "Period 2": period("L", 5, None)
+                },
+                "2023-09-26": {
+                    "AM": period("N", 0, None),
+                    "PM": period("I", 0, None),
+                    "Period 1": period("N", 0, Some("Maths")),
+                    "Period 2": period("I", 0, None)
+                },
+                "2024-01-08": {
+                    "AM": period("/", 0, None),
+                    "PM": period("#", 0, None)
+                }
+            },
+            "meta": {
+                "dates": ["2023-09-25", "2023-09-26", "2024-01-08"],
+                "sessions": ["AM", "PM", "Period 1", "Period 2"],
+                "start_date": "2023-09-01T00:00:00+00:00",
+                "end_date": "2024-01-31T00:00:00+00:00",
+                "percentage": "60",
+                "percentage_singe_august": "60"
+            }
+        }))
+        .unwrap();
+
+        let lesson: Lesson = serde_json::from_value(json!({
+            "teacher_name": "Teacher Name",
+            "lesson_name": "Lesson Name",
+            "subject_name": "English",
+            "is_alternative_lesson": false,
+            "period_name": "1Mon:P2",
+            "period_number": "P2",
+            "room_name": "Room Name",
+            "date": "2023-09-25",
+            "start_time": "2023-09-25T09:45:00+01:00",
+            "end_time": "2023-09-25T10:45:00+01:00",
+            "key": 1,
+            "note_abstract": "",
+            "note": "",
+            "pupil_note_abstract": "",
+            "pupil_note": "",
+            "pupil_note_raw": ""
+        }))
+        .unwrap();
+
+        let stats = AttendanceStats::with_lessons(&attendance, [&lesson]);
+
+        assert_eq!(
+            stats.overall,
+            AttendanceCounts {
+                present: 3,
+                late: 1,
+                authorised_absences: 1,
+                unauthorised_absences: 1,
+                late_minutes: 5,
+            }
+        );
+        assert_eq!(stats.overall.percentage(), Some(60.0));
+        assert!(stats.is_persistent_absence());
+
+        assert_eq!(stats.by_weekday[&Weekday::Mon].percentage(), Some(100.0));
+        assert_eq!(stats.by_weekday[&Weekday::Tue].absences(), 2);
+
+        let autumn = SchoolTerm { school_year: 2023, term: Term::Autumn };
+        let spring = SchoolTerm { school_year: 2023, term: Term::Spring };
+        assert_eq!(stats.by_term[&autumn].possible(), 4);
+        assert_eq!(stats.by_term[&spring].possible(), 1);
+
+        // the 26th's "Period 2" has no lesson_name or lesson, so has no subject
+        assert_eq!(stats.by_subject.len(), 2);
+        assert_eq!(stats.by_subject["Maths"].percentage(), Some(50.0));
+        assert_eq!(stats.by_subject["English"].late_minutes, 5);
+        assert_eq!(stats.persistent_absence_subjects(), vec!["Maths"]);
+
+        assert_eq!(normalize_period("Period Tut"), normalize_period("Tut"));
+        assert_eq!(normalize_period("Period 1"), normalize_period("P1"));
+    }
+
+    #[test]
+    fn attendance_stats_without_registration_test() {
+        let attendance: Attendance = serde_json::from_value(json!({
+            "success": 1,
+            "data": {
+                "2023-09-25": {
+                    "Period 1": {
+                        "code": "/",
+                        "status": "present",
+                        "late_minutes": 0,
+                        "lesson_name": "Maths"
+                    },
+                    "Period 2": {
+                        "code": "N",
+                        "status": "absent",
+                        "late_minutes": 0,
+                        "lesson_name": "English"
+                    }
+                }
+            },
+            "meta": {
+                "dates": ["2023-09-25"],
+                "sessions": ["Period 1", "Period 2"],
+                "start_date": "2023-09-01T00:00:00+00:00",
+                "end_date": "2023-09-30T00:00:00+00:00",
+                "percentage": "50",
+                "percentage_singe_august": "50"
+            }
+        }))
+        .unwrap();
+
+        let stats = AttendanceStats::new(&attendance);
+
+        assert_eq!(stats.overall.possible(), 2);
+        assert_eq!(stats.by_subject["Maths"].percentage(), Some(100.0));
+        assert_eq!(stats.by_subject["English"].percentage(), Some(0.0));
+    }
+
+    #[test]
+    fn attendance_stats_serde_test() {
+        let mut stats = AttendanceStats::default();
+        stats
+            .by_term
+            .insert(SchoolTerm { school_year: 2023, term: Term::Autumn }, AttendanceCounts::default());
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert!(json["by_term"]["2023-autumn"].is_object());
+
+        let parsed: AttendanceStats = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, stats);
+
+        assert!(serde_json::from_value::<SchoolTerm>(json!("2023-winter")).is_err());
+    }
+
+    #[test]
+    fn attendance_code_test() {
+        let code = |code: &str| AttendanceCode::from(code);
+
+        assert_eq!(code("/"), AttendanceCode::Present("/".to_string()));
+        assert_eq!(code("\\"), AttendanceCode::Present("\\".to_string()));
+        assert_eq!(code("L"), AttendanceCode::Late("L".to_string()));
+        assert_eq!(code("I"), AttendanceCode::AuthorisedAbsence("I".to_string()));
+        assert_eq!(code("C1"), AttendanceCode::AuthorisedAbsence("C1".to_string()));
+        assert_eq!(code("J1"), AttendanceCode::AuthorisedAbsence("J1".to_string()));
+        assert_eq!(code("J"), AttendanceCode::ApprovedEducationalActivity("J".to_string()));
+        assert_eq!(code("D"), AttendanceCode::NotRequired("D".to_string()));
+        assert_eq!(code("U"), AttendanceCode::UnauthorisedAbsence("U".to_string()));
+        assert_eq!(code("V"), AttendanceCode::ApprovedEducationalActivity("V".to_string()));
+        assert_eq!(code("Y2"), AttendanceCode::NotRequired("Y2".to_string()));
+        assert_eq!(code(""), AttendanceCode::Unknown("".to_string()));
+        assert_eq!(code("?"), AttendanceCode::Unknown("?".to_string()));
+
+        assert!(code("L").is_present());
+        assert!(code("N").is_absent());
+        assert!(!code("X").is_counted());
+
+        let period: AttendancePeriod = serde_json::from_value(json!({
+            "code": "N",
+            "status": "authorised_absence",
+            "late_minutes": 0
+        }))
+        .unwrap();
+
+        assert_eq!(period.status, AttendancePeriodStatus::Other("authorised_absence".to_string()));
+        assert_eq!(serde_json::to_value(&period).unwrap()["code"], "N");
+        assert_eq!(serde_json::to_value(&period).unwrap()["status"], "authorised_absence");
+    }
+
+    #[test]
+    fn late_minutes_test() {
+        let late_minutes = |value: Value| serde_json::from_value::<LateMinutes>(value);
+
+        assert_eq!(late_minutes(json!(5)).unwrap().minutes(), Some(5));
+        assert_eq!(late_minutes(json!("5")).unwrap().minutes(), Some(5));
+        assert_eq!(late_minutes(json!("")).unwrap().minutes(), None);
+        assert_eq!(late_minutes(json!(null)).unwrap(), LateMinutes::new(None));
+        assert!(late_minutes(json!("five")).is_err());
+        assert!(late_minutes(json!(-1)).is_err());
+
+        // the original form is kept
+        for value in [json!(5), json!("5"), json!("")] {
+            assert_eq!(serde_json::to_value(late_minutes(value.clone()).unwrap()).unwrap(), value);
+        }
+    }
 }
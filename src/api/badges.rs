@@ -1,44 +1,50 @@
-use serde::Deserialize;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
+use crate::api::dates::deserialize_datetime;
+use crate::client::{ErrorResponse, SuccessResponse, Client};
 
 use super::helpers::Empty;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Teacher {
     pub title: String,
     pub first_name: String,
     pub last_name: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LessonPupilBehaviour {
     pub reason: String,
     pub score: usize,
     pub polarity: String,
-    pub timestamp: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub timestamp: NaiveDateTime,
     pub teacher: Teacher,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PupilEvent {
     pub label: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PupilBadge {
-    pub timestamp: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub timestamp: NaiveDateTime,
     pub lesson_pupil_behaviour: LessonPupilBehaviour,
     pub event: PupilEvent,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Badge {
     pub id: usize,
     pub name: String,
     pub icon: String,
     pub colour: String,
-    pub created_date: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub created_date: NaiveDateTime,
     pub pupil_badges: Vec<PupilBadge>,
     pub icon_url: String,
 }
@@ -49,14 +55,10 @@ pub type Badges = SuccessResponse<BadgesData, BadgesMeta>;
 
 impl Client {
     /// Gets the current student's earned badges 
-    pub async fn get_badges(&mut self) -> Result<Badges, ErrorResponse> {
-        let request = self
-            .build_get(format!("/eventbadges/{}", self.student_id))
-            .await?
-            .send()
+    pub async fn get_badges(&self) -> Result<Badges, ErrorResponse> {
+        let text = self
+            .request(Method::GET, format!("/eventbadges/{}", self.student_id), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Badges = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -112,7 +114,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_badges().await.unwrap();
 
@@ -1,26 +1,30 @@
 use std::collections::HashMap;
 
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
-use chrono::NaiveDate;
-use serde::Deserialize;
+use crate::api::dates::{deserialize_date, deserialize_datetime};
+use crate::client::{ErrorResponse, SuccessResponse, Client};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BehaviourStyle {
     pub border_color: Option<String>,
     pub custom_class: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BehaviourTimelinePoint {
     pub positive: usize,
     pub negative: isize,
     pub name: String,
-    pub start: String,
-    pub end: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub start: NaiveDate,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub end: NaiveDate,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BehaviourData {
     pub timeline: Vec<BehaviourTimelinePoint>,
     #[serde(deserialize_with = "deserialize_hashmap_or_empty_array")]
@@ -55,10 +59,12 @@ where
     Err(serde::de::Error::custom("Invalid JSON format for 'data'"))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BehaviourMeta {
-    pub start_date: String,
-    pub end_date: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub start_date: NaiveDateTime,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub end_date: NaiveDateTime,
     pub step_size: String,
 }
 
@@ -84,7 +90,7 @@ impl Client {
     /// ));
     /// ```
     pub async fn get_behaviour(
-        &mut self,
+        &self,
         options: Option<BehaviourOptions>,
     ) -> Result<Behaviour, ErrorResponse> {
         let mut params = url::form_urlencoded::Serializer::new(String::new());
@@ -100,13 +106,9 @@ impl Client {
 
         let params = params.finish();
 
-        let request = self
-            .build_get(format!("/behaviour/{}?{}", self.student_id, params))
-            .await?
-            .send()
+        let text = self
+            .request(Method::GET, format!("/behaviour/{}?{}", self.student_id, params), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Behaviour = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -174,7 +176,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_behaviour(None).await.unwrap();
 
@@ -0,0 +1,245 @@
+//! Parsing for the dates and times ClassCharts returns.
+//!
+//! ClassCharts is only used by schools in the UK, and returns times in a mix of formats: some are
+//! RFC 3339 with an offset (`2023-09-26T14:05:00+01:00`, or midnight UK time written in UTC as
+//! `2023-08-24T23:00:00+00:00`), and some are UK local times without an offset
+//! (`2023-04-21 10:00:00`). Every date and time is converted to UK local time (`Europe/London`),
+//! so they can be compared with each other and with the dates passed to the API. The exception
+//! is the bounds of a day, like `2023-09-26T00:00:00+00:00` to `2023-09-26T23:59:59+00:00`,
+//! which are the UK day written with a UTC offset, so are parsed as they are written.
+
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{de::Error, Deserialize, Deserializer};
+
+/// The timezone ClassCharts times are converted to.
+pub const UK_TIMEZONE: Tz = chrono_tz::Europe::London;
+
+/// Today's date in the UK.
+pub fn uk_today() -> NaiveDate {
+    return Utc::now().with_timezone(&UK_TIMEZONE).date_naive();
+}
+
+/// Attaches the UK timezone to a time returned by ClassCharts. When the clocks go back, the
+/// earlier of the two times is used, and times skipped when the clocks go forward return `None`.
+pub fn to_uk_datetime(datetime: NaiveDateTime) -> Option<DateTime<Tz>> {
+    return match UK_TIMEZONE.from_local_datetime(&datetime) {
+        LocalResult::Single(datetime) => Some(datetime),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest),
+        LocalResult::None => None,
+    };
+}
+
+/// Parses a date and time, converting it to UK local time if it has an offset.
+pub(crate) fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&UK_TIMEZONE).naive_local());
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(datetime);
+        }
+    }
+
+    return None;
+}
+
+/// Parses a date and time as it is written, ignoring any offset.
+pub(crate) fn parse_wall_datetime(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.naive_local());
+    }
+
+    return parse_datetime(value);
+}
+
+/// Parses a date, or the UK date of a date and time.
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    if let Some(datetime) = parse_datetime(value) {
+        return Some(datetime.date());
+    }
+
+    // detentions have dates like `2023-08-24:00:00+00:00`
+    return value
+        .get(..10)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+}
+
+/// Parses a time of day, with or without seconds.
+pub(crate) fn parse_time(value: &str) -> Option<NaiveTime> {
+    return NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok();
+}
+
+/// ClassCharts sometimes sends an empty string instead of `null`.
+fn deserialize_optional_str<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Deserialize::deserialize(deserializer)?;
+
+    return Ok(value.filter(|value| !value.trim().is_empty()));
+}
+
+pub(crate) fn deserialize_datetime<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+
+    return parse_datetime(&value)
+        .ok_or_else(|| D::Error::custom(format!("invalid date and time: {}", value)));
+}
+
+/// Deserializes the bounds of a day, with `parse_wall_datetime`.
+pub(crate) fn deserialize_wall_datetime<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+
+    return parse_wall_datetime(&value)
+        .ok_or_else(|| D::Error::custom(format!("invalid date and time: {}", value)));
+}
+
+pub(crate) fn deserialize_optional_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    return deserialize_optional_str(deserializer)?
+        .map(|value| {
+            parse_datetime(&value)
+                .ok_or_else(|| D::Error::custom(format!("invalid date and time: {}", value)))
+        })
+        .transpose();
+}
+
+pub(crate) fn deserialize_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+
+    return parse_date(&value).ok_or_else(|| D::Error::custom(format!("invalid date: {}", value)));
+}
+
+pub(crate) fn deserialize_optional_date<'de, D>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    return deserialize_optional_str(deserializer)?
+        .map(|value| {
+            parse_date(&value).ok_or_else(|| D::Error::custom(format!("invalid date: {}", value)))
+        })
+        .transpose();
+}
+
+pub(crate) fn deserialize_dates<'de, D>(deserializer: D) -> Result<Vec<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    return Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| {
+            parse_date(value).ok_or_else(|| D::Error::custom(format!("invalid date: {}", value)))
+        })
+        .collect();
+}
+
+pub(crate) fn deserialize_time<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+
+    return parse_time(&value).ok_or_else(|| D::Error::custom(format!("invalid time: {}", value)));
+}
+
+pub(crate) fn deserialize_optional_time<'de, D>(
+    deserializer: D,
+) -> Result<Option<NaiveTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    return deserialize_optional_str(deserializer)?
+        .map(|value| {
+            parse_time(&value).ok_or_else(|| D::Error::custom(format!("invalid time: {}", value)))
+        })
+        .transpose();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(date: &str, time: &str) -> NaiveDateTime {
+        return NaiveDateTime::new(
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap(),
+        );
+    }
+
+    #[test]
+    fn parse_datetime_test() {
+        // lesson times are already UK time
+        assert_eq!(
+            parse_datetime("2023-09-26T14:05:00+01:00"),
+            Some(datetime("2023-09-26", "14:05:00"))
+        );
+        // midnight UK time during BST, written in UTC
+        assert_eq!(
+            parse_datetime("2023-08-24T23:00:00+00:00"),
+            Some(datetime("2023-08-25", "00:00:00"))
+        );
+        // UTC is UK time outside of BST
+        assert_eq!(
+            parse_datetime("2023-02-23T10:00:00+00:00"),
+            Some(datetime("2023-02-23", "10:00:00"))
+        );
+        // activity timestamps have no offset
+        assert_eq!(
+            parse_datetime("2023-04-21 10:00:00"),
+            Some(datetime("2023-04-21", "10:00:00"))
+        );
+        assert_eq!(parse_datetime("yesterday"), None);
+    }
+
+    #[test]
+    fn parse_date_test() {
+        let date = NaiveDate::from_ymd_opt(2023, 8, 24);
+
+        assert_eq!(parse_date("2023-08-24"), date);
+        assert_eq!(parse_date("2023-08-24:00:00+00:00"), date);
+        assert_eq!(parse_date("2023-08-23T23:00:00+00:00"), date);
+        assert_eq!(parse_date("24/08/2023"), None);
+    }
+
+    #[test]
+    fn parse_time_test() {
+        assert_eq!(parse_time("16:00"), NaiveTime::from_hms_opt(16, 0, 0));
+        assert_eq!(parse_time("08:45:00"), NaiveTime::from_hms_opt(8, 45, 0));
+        assert_eq!(parse_time("4pm"), None);
+    }
+
+    #[test]
+    fn to_uk_datetime_test() {
+        let summer = to_uk_datetime(datetime("2023-09-26", "14:05:00")).unwrap();
+        assert_eq!(summer.to_rfc3339(), "2023-09-26T14:05:00+01:00");
+
+        let winter = to_uk_datetime(datetime("2023-02-23", "10:00:00")).unwrap();
+        assert_eq!(winter.to_rfc3339(), "2023-02-23T10:00:00+00:00");
+
+        // the clocks went forward at 01:00 on the 26th of March 2023
+        assert_eq!(to_uk_datetime(datetime("2023-03-26", "01:30:00")), None);
+    }
+}
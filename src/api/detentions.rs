@@ -1,10 +1,15 @@
 use crate::{
-    api::helpers::deserialize_yes_no_bool,
-    client::{ErrorResponse, CCParser, SuccessResponse, Client},
+    api::{
+        dates::{deserialize_optional_date, deserialize_optional_time},
+        helpers::{deserialize_yes_no_bool, serialize_yes_no_bool},
+    },
+    client::{ErrorResponse, SuccessResponse, Client},
 };
-use serde::Deserialize;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum DetentionAttended {
     #[serde(rename = "yes")]
     Yes,
@@ -16,17 +21,26 @@ pub enum DetentionAttended {
     Pending,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct School {
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub opt_notes_names: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub opt_notes_comments: bool,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub opt_notes_comments_pupils: bool,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Pupil {
     pub id: usize,
     pub first_name: String,
@@ -34,25 +48,25 @@ pub struct Pupil {
     pub school: School,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Subject {
     pub id: usize,
     pub name: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Lesson {
     pub id: usize,
     pub name: String,
     pub subject: Subject,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LessonPupilBehaviour {
     pub reason: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Teacher {
     pub id: usize,
     pub first_name: String,
@@ -60,20 +74,22 @@ pub struct Teacher {
     pub title: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DetentionType {
     pub name: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Detention {
     pub id: usize,
     pub attended: DetentionAttended,
-    pub date: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
+    pub date: Option<NaiveDate>,
     pub length: Option<usize>,
     pub location: Option<String>,
     pub notes: Option<String>,
-    pub time: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub time: Option<NaiveTime>,
     pub pupil: Pupil,
     pub lesson: Option<Lesson>,
     pub lesson_pupil_behaviour: LessonPupilBehaviour,
@@ -83,7 +99,7 @@ pub struct Detention {
 
 pub type DetentionsData = Vec<Detention>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DetentionsMeta {
     pub detention_alias_plural: String,
 }
@@ -92,14 +108,10 @@ pub type Detentions = SuccessResponse<DetentionsData, DetentionsMeta>;
 
 impl Client {
     /// Gets the current student's detentions. 
-    pub async fn get_detentions(&mut self) -> Result<Detentions, ErrorResponse> {
-        let request = self
-            .build_get(format!("/detentions/{}", self.student_id))
-            .await?
-            .send()
+    pub async fn get_detentions(&self) -> Result<Detentions, ErrorResponse> {
+        let text = self
+            .request(Method::GET, format!("/detentions/{}", self.student_id), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Detentions = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -171,9 +183,12 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
+
+        let detentions = client.get_detentions().await.unwrap();
 
-        let _ = client.get_detentions().await.unwrap();
+        assert_eq!(detentions.data[0].date, NaiveDate::from_ymd_opt(2023, 8, 24));
+        assert_eq!(detentions.data[0].time, NaiveTime::from_hms_opt(16, 0, 0));
 
         detentions_response.assert();
     }
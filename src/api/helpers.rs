@@ -1,8 +1,9 @@
 use std::fmt;
 
+use serde_json::Value;
 use serde::{
     de::{Deserializer, Error, Visitor},
-    Deserialize,
+    Deserialize, Serialize, Serializer,
 };
 
 pub fn deserialize_yes_no_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
@@ -33,5 +34,82 @@ where
     deserializer.deserialize_str(BoolVisitor)
 }
 
-#[derive(Deserialize, Debug)]
+/// Serializes a bool as `"yes"` or `"no"`, the same as ClassCharts.
+pub fn serialize_yes_no_bool<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(if *value { "yes" } else { "no" })
+}
+
+/// Deserializes a number ClassCharts sends as either a JSON number or a string. `null` or an
+/// empty string is `None`.
+pub fn deserialize_optional_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Value = Deserialize::deserialize(deserializer)?;
+
+    return match value {
+        Value::Null => Ok(None),
+        Value::Number(number) => Ok(number.as_f64()),
+        Value::String(string) if string.trim().is_empty() => Ok(None),
+        Value::String(string) => string
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| D::Error::custom(format!("invalid number: {}", string))),
+        value => Err(D::Error::custom(format!("invalid number: {}", value))),
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Empty {}
+
+/// How ClassCharts sent a number, so it can be serialized the same way it was received.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) enum WireNumber {
+    #[default]
+    Number,
+    String(String),
+}
+
+impl WireNumber {
+    /// Parses a number sent as either a JSON number or a string. An empty string or `null` is
+    /// `None`, and anything else which isn't a whole number fits in a `u32` is an error.
+    pub(crate) fn parse(value: Value, field: &str) -> Result<(Option<u32>, WireNumber), String> {
+        let invalid = |value: &dyn fmt::Display| format!("invalid {}: {}", field, value);
+
+        return match value {
+            Value::Null => Ok((None, WireNumber::Number)),
+            Value::Number(number) => {
+                let number = number
+                    .as_u64()
+                    .and_then(|number| u32::try_from(number).ok())
+                    .ok_or_else(|| invalid(&number))?;
+
+                Ok((Some(number), WireNumber::Number))
+            }
+            Value::String(string) if string.trim().is_empty() => {
+                Ok((None, WireNumber::String(string)))
+            }
+            Value::String(string) => {
+                let number = string.trim().parse().map_err(|_| invalid(&string))?;
+
+                Ok((Some(number), WireNumber::String(string)))
+            }
+            value => Err(invalid(&value)),
+        };
+    }
+
+    pub(crate) fn serialize<S>(&self, number: Option<u32>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        return match (self, number) {
+            (WireNumber::String(string), _) => serializer.serialize_str(string),
+            (WireNumber::Number, Some(number)) => serializer.serialize_u32(number),
+            (WireNumber::Number, None) => serializer.serialize_none(),
+        };
+    }
+}
@@ -1,10 +1,21 @@
-use crate::api::helpers::deserialize_yes_no_bool;
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
-use chrono::NaiveDate;
-use serde::Deserialize;
+use crate::api::dates::{
+    deserialize_date, deserialize_optional_datetime, deserialize_time, uk_today,
+};
+use crate::api::helpers::{
+    deserialize_optional_f64, deserialize_yes_no_bool, serialize_yes_no_bool,
+};
+use crate::client::{ErrorResponse, SuccessResponse, Client};
+use crate::multipart::Multipart;
+use crate::new_params;
+use std::collections::BTreeMap;
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+use super::helpers::Empty;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HomeworkState {
     #[serde(rename = "not_completed")]
     NotCompleted,
@@ -14,22 +25,205 @@ pub enum HomeworkState {
     Completed,
 }
 
-#[derive(Deserialize, Debug)]
+/// The mark a teacher has given a piece of homework.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum HomeworkMark {
+    /// The homework hasn't been marked.
+    #[default]
+    None,
+    /// A score, e.g. `8/10`. `out_of` is `None` when ClassCharts only returns the score.
+    Numeric { score: f64, out_of: Option<f64> },
+    /// A percentage, e.g. `80%`.
+    Percentage(f64),
+    /// Any other mark, such as a letter grade.
+    Grade(String),
+    /// A mark which isn't a number or a string, kept as ClassCharts returned it.
+    Other(Value),
+}
+
+impl HomeworkMark {
+    fn parse(mark: &str) -> HomeworkMark {
+        let mark = mark.trim();
+
+        if mark.is_empty() {
+            return HomeworkMark::None;
+        }
+
+        if let Some(percentage) = mark.strip_suffix('%') {
+            if let Ok(percentage) = percentage.trim().parse() {
+                return HomeworkMark::Percentage(percentage);
+            }
+        }
+
+        if let Some((score, out_of)) = mark.split_once('/') {
+            if let (Ok(score), Ok(out_of)) = (score.trim().parse(), out_of.trim().parse()) {
+                return HomeworkMark::Numeric {
+                    score,
+                    out_of: Some(out_of),
+                };
+            }
+        }
+
+        if let Ok(score) = mark.parse() {
+            return HomeworkMark::Numeric {
+                score,
+                out_of: None,
+            };
+        }
+
+        return HomeworkMark::Grade(mark.to_string());
+    }
+
+    pub fn is_marked(&self) -> bool {
+        return *self != HomeworkMark::None;
+    }
+
+    /// The mark as a percentage, if it is a percentage or a score out of a total.
+    pub fn percentage(&self) -> Option<f64> {
+        match self {
+            HomeworkMark::Percentage(percentage) => Some(*percentage),
+            HomeworkMark::Numeric {
+                score,
+                out_of: Some(out_of),
+            } if *out_of > 0.0 => Some(score / out_of * 100.0),
+            _ => None,
+        }
+    }
+
+    /// The score, if the mark is numeric.
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            HomeworkMark::Numeric { score, .. } => Some(*score),
+            _ => None,
+        }
+    }
+
+    /// The total the score is out of, if ClassCharts returned one.
+    pub fn out_of(&self) -> Option<f64> {
+        match self {
+            HomeworkMark::Numeric { out_of, .. } => *out_of,
+            _ => None,
+        }
+    }
+
+    /// The grade, if the mark isn't numeric.
+    pub fn grade(&self) -> Option<&str> {
+        match self {
+            HomeworkMark::Grade(grade) => Some(grade),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HomeworkMark {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: Value = Deserialize::deserialize(deserializer)?;
+
+        match value {
+            Value::Null => Ok(HomeworkMark::None),
+            Value::Number(num) => Ok(HomeworkMark::Numeric {
+                score: num.as_f64().unwrap_or_default(),
+                out_of: None,
+            }),
+            Value::String(s) => Ok(HomeworkMark::parse(&s)),
+            value => Ok(HomeworkMark::Other(value)),
+        }
+    }
+}
+
+impl Serialize for HomeworkMark {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            HomeworkMark::None => serializer.serialize_none(),
+            HomeworkMark::Numeric {
+                score,
+                out_of: None,
+            } => serializer.serialize_f64(*score),
+            HomeworkMark::Numeric {
+                score,
+                out_of: Some(out_of),
+            } => serializer.serialize_str(&format!("{}/{}", score, out_of)),
+            HomeworkMark::Percentage(percentage) => {
+                serializer.serialize_str(&format!("{}%", percentage))
+            }
+            HomeworkMark::Grade(grade) => serializer.serialize_str(grade),
+            HomeworkMark::Other(value) => value.serialize(serializer),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HomeworkStatus {
     pub id: usize,
     pub state: Option<HomeworkState>,
-    pub mark: Value,
-    pub mark_relative: usize,
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(default)]
+    pub mark: HomeworkMark,
+    /// ClassCharts' own percentage for the mark. This is `0` when the homework hasn't been
+    /// marked, so `mark_percentage` should usually be used instead.
+    #[serde(default, deserialize_with = "deserialize_optional_f64")]
+    pub mark_relative: Option<f64>,
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub ticked: bool,
     pub allow_attachments: bool,
-    pub first_seen_date: Option<String>,
-    pub last_seen_date: Option<String>,
-    pub attachments: Vec<Value>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    pub first_seen_date: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    pub last_seen_date: Option<NaiveDateTime>,
+    pub attachments: Vec<HomeworkAttachment>,
     pub has_feedback: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<String>,
+}
+
+impl HomeworkStatus {
+    /// The mark as a percentage, falling back to `mark_relative` when the mark is marked but
+    /// isn't a percentage or a score out of a total, such as a letter grade.
+    pub fn mark_percentage(&self) -> Option<f64> {
+        if !self.mark.is_marked() {
+            return None;
+        }
+
+        return self.mark.percentage().or(self.mark_relative);
+    }
+
+    /// The teacher's feedback, if `has_feedback` is set and ClassCharts returned it.
+    pub fn feedback(&self) -> Option<&str> {
+        if !self.has_feedback {
+            return None;
+        }
+
+        return self
+            .feedback
+            .as_deref()
+            .filter(|feedback| !feedback.trim().is_empty());
+    }
+}
+
+/// A file the student has attached to their homework submission.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HomeworkAttachment {
+    pub id: usize,
+    pub file_name: String,
+    pub file: String,
+    pub validated_file: String,
+    #[serde(default)]
+    pub teacher_note: Option<String>,
+    #[serde(default)]
+    pub teacher_file: Option<String>,
+    #[serde(default)]
+    pub validated_teacher_file: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ValidatedHomeworkAttachment {
     pub id: usize,
     pub file_name: String,
@@ -37,7 +231,7 @@ pub struct ValidatedHomeworkAttachment {
     pub validated_file: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Homework {
     pub lesson: String,
     pub subject: String,
@@ -47,17 +241,20 @@ pub struct Homework {
     pub title: String,
     pub meta_title: String,
     pub description: String,
-    pub issue_date: String,
-    pub due_date: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub issue_date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub due_date: NaiveDate,
     pub completion_time_unit: String,
     pub completion_time_value: String,
-    pub publish_time: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub publish_time: NaiveTime,
     pub status: HomeworkStatus,
     pub validated_links: Vec<Value>,
     pub validated_attachments: Vec<ValidatedHomeworkAttachment>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisplayDate {
     #[serde(rename = "due_date")]
     DueDate,
@@ -65,16 +262,31 @@ pub enum DisplayDate {
     IssueDate,
 }
 
+impl DisplayDate {
+    /// The value ClassCharts uses for this display date.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayDate::DueDate => "due_date",
+            DisplayDate::IssueDate => "issue_date",
+        }
+    }
+}
+
+/// Which homework `get_homeworks` returns. `from` and `to` are compared against the due date or
+/// the issue date, depending on `display_date` (ClassCharts uses the due date by default).
+#[derive(Debug, Clone, Default)]
 pub struct HomeworkOptions {
     pub display_date: Option<DisplayDate>,
     pub from: Option<NaiveDate>,
     pub to: Option<NaiveDate>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HomeworkMeta {
-    pub start_date: String,
-    pub end_date: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub start_date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub end_date: NaiveDate,
     pub display_type: DisplayDate,
     pub max_files_allowed: usize,
     pub allowed_file_types: Vec<String>,
@@ -89,6 +301,238 @@ pub type HomeworkData = Vec<Homework>;
 
 pub type Homeworks = SuccessResponse<HomeworkData, HomeworkMeta>;
 
+/// The marks for one subject, created by `marks_by_subject`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SubjectMarks {
+    /// How many pieces of homework were set, marked or not.
+    pub homework_count: usize,
+    /// The marks of the homework which has been marked.
+    pub marks: Vec<HomeworkMark>,
+    /// The `mark_percentage` of each piece of homework which has one.
+    pub percentages: Vec<f64>,
+}
+
+impl SubjectMarks {
+    /// The average of the `percentages`.
+    pub fn average_percentage(&self) -> Option<f64> {
+        if self.percentages.is_empty() {
+            return None;
+        }
+
+        return Some(self.percentages.iter().sum::<f64>() / self.percentages.len() as f64);
+    }
+}
+
+/// Groups the marks of each piece of homework by subject.
+///
+/// Example:
+/// ```ignore
+/// let homeworks = client.get_homeworks(None).await?;
+///
+/// for (subject, marks) in marks_by_subject(&homeworks.data) {
+///     println!("{}: {:?}%", subject, marks.average_percentage());
+/// }
+/// ```
+pub fn marks_by_subject(homeworks: &[Homework]) -> BTreeMap<String, SubjectMarks> {
+    let mut subjects: BTreeMap<String, SubjectMarks> = BTreeMap::new();
+
+    for homework in homeworks {
+        let subject = subjects.entry(homework.subject.clone()).or_default();
+        subject.homework_count += 1;
+
+        if homework.status.mark.is_marked() {
+            subject.marks.push(homework.status.mark.clone());
+        }
+
+        if let Some(percentage) = homework.status.mark_percentage() {
+            subject.percentages.push(percentage);
+        }
+    }
+
+    return subjects;
+}
+
+/// A homework query, which combines the date range ClassCharts filters by with filters which are
+/// applied to the returned homework. Run it with `Client::query_homeworks`.
+///
+/// ClassCharts filters by either the due date or the issue date, so only one date range can be
+/// set. Each of the `due_*` and `issued_*` methods replaces the range set before it.
+///
+/// Example:
+/// ```ignore
+/// // Maths homework due this week which hasn't been done yet.
+/// let query = HomeworkQuery::new()
+///     .due_this_week()
+///     .outstanding()
+///     .subject("Maths");
+///
+/// let homeworks = client.query_homeworks(&query).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HomeworkQuery {
+    options: HomeworkOptions,
+    outstanding: bool,
+    subject: Option<String>,
+    teacher: Option<String>,
+}
+
+impl HomeworkQuery {
+    pub fn new() -> HomeworkQuery {
+        return HomeworkQuery::default();
+    }
+
+    /// Homework due between two dates, inclusive. This replaces any date range already set.
+    pub fn due_between(mut self, from: NaiveDate, to: NaiveDate) -> HomeworkQuery {
+        self.options = HomeworkOptions {
+            display_date: Some(DisplayDate::DueDate),
+            from: Some(from),
+            to: Some(to),
+        };
+        return self;
+    }
+
+    /// Homework due in the week (Monday to Sunday) containing `date`.
+    pub fn due_in_week_of(self, date: NaiveDate) -> HomeworkQuery {
+        let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+
+        return self.due_between(monday, monday + Days::new(6));
+    }
+
+    /// Homework due this week, in UK time.
+    pub fn due_this_week(self) -> HomeworkQuery {
+        return self.due_in_week_of(uk_today());
+    }
+
+    /// Homework issued between two dates, inclusive. This replaces any date range already set.
+    pub fn issued_between(mut self, from: NaiveDate, to: NaiveDate) -> HomeworkQuery {
+        self.options = HomeworkOptions {
+            display_date: Some(DisplayDate::IssueDate),
+            from: Some(from),
+            to: Some(to),
+        };
+        return self;
+    }
+
+    /// Homework issued from `date` until today, in UK time.
+    pub fn issued_since(self, date: NaiveDate) -> HomeworkQuery {
+        return self.issued_between(date, uk_today());
+    }
+
+    /// Only homework which hasn't been ticked or completed.
+    pub fn outstanding(mut self) -> HomeworkQuery {
+        self.outstanding = true;
+        return self;
+    }
+
+    /// Only homework for a subject. This is case insensitive.
+    pub fn subject<S>(mut self, subject: S) -> HomeworkQuery
+    where
+        S: Into<String>,
+    {
+        self.subject = Some(subject.into());
+        return self;
+    }
+
+    /// Only homework set by a teacher. This is case insensitive.
+    pub fn teacher<T>(mut self, teacher: T) -> HomeworkQuery
+    where
+        T: Into<String>,
+    {
+        self.teacher = Some(teacher.into());
+        return self;
+    }
+
+    /// The options which are sent to ClassCharts.
+    pub fn options(&self) -> HomeworkOptions {
+        return self.options.clone();
+    }
+
+    /// Whether a piece of homework passes the filters which aren't sent to ClassCharts.
+    pub fn matches(&self, homework: &Homework) -> bool {
+        if self.outstanding
+            && (homework.status.ticked || homework.status.state == Some(HomeworkState::Completed))
+        {
+            return false;
+        }
+
+        if let Some(subject) = &self.subject {
+            if !homework.subject.eq_ignore_ascii_case(subject) {
+                return false;
+            }
+        }
+
+        if let Some(teacher) = &self.teacher {
+            if !homework.teacher.eq_ignore_ascii_case(teacher) {
+                return false;
+            }
+        }
+
+        return true;
+    }
+}
+
+pub type HomeworkDetailMeta = Vec<Empty>;
+
+pub type HomeworkDetail = SuccessResponse<Homework, HomeworkDetailMeta>;
+
+pub type HomeworkTickedMeta = Vec<Empty>;
+
+pub type HomeworkTicked = SuccessResponse<HomeworkStatus, HomeworkTickedMeta>;
+
+pub type HomeworkAttachmentUploadMeta = Vec<Empty>;
+
+pub type HomeworkAttachmentUpload = SuccessResponse<HomeworkAttachment, HomeworkAttachmentUploadMeta>;
+
+pub type HomeworkAttachmentDeleteData = Vec<Empty>;
+
+pub type HomeworkAttachmentDeleteMeta = Vec<Empty>;
+
+pub type HomeworkAttachmentDelete =
+    SuccessResponse<HomeworkAttachmentDeleteData, HomeworkAttachmentDeleteMeta>;
+
+/// Checks that a file can be attached to the homework, before it is uploaded.
+fn validate_attachment(
+    status: &HomeworkStatus,
+    meta: &HomeworkMeta,
+    file_name: &str,
+) -> Result<(), ErrorResponse> {
+    if !meta.allow_attachments || !status.allow_attachments {
+        return Err(ErrorResponse::InvalidAttachmentError(
+            "this homework does not allow attachments".to_string(),
+        ));
+    }
+
+    if status.attachments.len() >= meta.max_files_allowed {
+        return Err(ErrorResponse::InvalidAttachmentError(format!(
+            "only {} files can be attached",
+            meta.max_files_allowed
+        )));
+    }
+
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    // an empty list means any type of file is allowed, and types may be written as `pdf` or
+    // `.pdf`
+    if !meta.allowed_file_types.is_empty()
+        && !meta.allowed_file_types.iter().any(|file_type| {
+            let file_type = file_type.trim();
+            let file_type = file_type.strip_prefix('.').unwrap_or(file_type);
+
+            return file_type.eq_ignore_ascii_case(&extension);
+        })
+    {
+        return Err(ErrorResponse::InvalidAttachmentError(format!(
+            "files of type \".{}\" are not allowed",
+            extension
+        )));
+    }
+
+    return Ok(());
+}
+
 impl Client {
     /// Gets the current student's homework 
     /// This is using `chrono` for parsing the date.
@@ -96,21 +540,26 @@ impl Client {
     /// Example:
     /// ```ignore
     /// // Gets homework due in from the current day till the next day.
-    /// client.get_homework(Some(
+    /// let today = classcharts::api::dates::uk_today();
+    ///
+    /// client.get_homeworks(Some(
     ///     HomeworkOptions {
     ///         display_date: Some(DisplayDate::DueDate),
-    ///         from: Some(chrono::Utc::now().date()),
-    ///         to: Some(chrono::Utc::now().checked_add_days(chrono::Days(1)).date()),
+    ///         from: Some(today),
+    ///         to: today.succ_opt(),
     ///     }
     /// ));
     /// ```
     pub async fn get_homeworks(
-        &mut self,
+        &self,
         options: Option<HomeworkOptions>,
     ) -> Result<Homeworks, ErrorResponse> {
         let mut params = url::form_urlencoded::Serializer::new(String::new());
  
         if let Some(options) = options {
+            if let Some(display_date) = options.display_date {
+                params.append_pair("display_date", display_date.as_str());
+            }
             if let Some(to) = options.to {
                 params.append_pair("to", &to.format("%Y-%m-%d").to_string());
             }
@@ -121,17 +570,115 @@ impl Client {
 
         let params = params.finish();
 
-        let request = self
-            .build_get(format!("/homeworks/{}?{}", self.student_id, params))
-            .await?
-            .send()
+        let text = self
+            .request(Method::GET, format!("/homeworks/{}?{}", self.student_id, params), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Homeworks = serde_json::from_str(&text)?;
 
         return Ok(data);
     }
+
+    /// Gets a single piece of the current student's homework, including its mark, the teacher's
+    /// feedback (see `HomeworkStatus::feedback`) and the files the student has attached.
+    /// The `homework_id` is the `id` of the homework, not its `status`.
+    pub async fn get_homework(&self, homework_id: usize) -> Result<HomeworkDetail, ErrorResponse> {
+        let params = new_params!("pupil_id", &self.student_id);
+
+        let text = self
+            .request(Method::GET, format!("/homework/{}?{}", homework_id, params), None)
+            .await?;
+        let data: HomeworkDetail = serde_json::from_str(&text)?;
+
+        return Ok(data);
+    }
+
+    /// Gets the current student's homework matching a `HomeworkQuery`.
+    /// The `meta` is returned unchanged, so its counts include homework which was filtered out.
+    pub async fn query_homeworks(&self, query: &HomeworkQuery) -> Result<Homeworks, ErrorResponse> {
+        let mut homeworks = self.get_homeworks(Some(query.options())).await?;
+        homeworks.data.retain(|homework| query.matches(homework));
+
+        return Ok(homeworks);
+    }
+
+    /// Ticks (or unticks) a piece of the current student's homework, marking it as completed.
+    /// The `homework_status_id` is the `id` of the homework's `status`, not the homework itself.
+    /// Returns the updated status.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let homework = &client.get_homeworks(None).await?.data[0];
+    /// client.tick_homework(homework.status.id, true).await?;
+    /// ```
+    pub async fn tick_homework(
+        &self,
+        homework_status_id: usize,
+        ticked: bool,
+    ) -> Result<HomeworkTicked, ErrorResponse> {
+        let params = new_params!(
+            "pupil_id", &self.student_id,
+            "ticked", if ticked { "yes" } else { "no" }
+        );
+
+        let text = self
+            .request(Method::POST, format!("/homeworkticked/{}", homework_status_id), Some(params))
+            .await?;
+        let data: HomeworkTicked = serde_json::from_str(&text)?;
+
+        return Ok(data);
+    }
+
+    /// Attaches a file to the current student's homework submission.
+    /// The `status` and `meta` come from `get_homeworks`, and are used to check that the homework
+    /// allows attachments, that the file type is allowed and that no more than
+    /// `max_files_allowed` files are attached, before anything is uploaded.
+    /// This is never retried automatically, as a retry could attach the file twice.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let homeworks = client.get_homeworks(None).await?;
+    /// let status = &homeworks.data[0].status;
+    ///
+    /// client
+    ///     .upload_homework_attachment(status, &homeworks.meta, "essay.pdf", std::fs::read("essay.pdf")?)
+    ///     .await?;
+    /// ```
+    pub async fn upload_homework_attachment(
+        &self,
+        status: &HomeworkStatus,
+        meta: &HomeworkMeta,
+        file_name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<HomeworkAttachmentUpload, ErrorResponse> {
+        validate_attachment(status, meta, file_name)?;
+
+        let form = Multipart::new()
+            .text("pupil_id", &self.student_id)
+            .file("file", file_name, "application/octet-stream", &bytes);
+
+        let text = self
+            .request_multipart(format!("/uploadhomeworkattachment/{}", status.id), form)
+            .await?;
+        let data: HomeworkAttachmentUpload = serde_json::from_str(&text)?;
+
+        return Ok(data);
+    }
+
+    /// Removes a file from the current student's homework submission.
+    /// The `attachment_id` is the `id` of one of the status' `attachments`.
+    pub async fn delete_homework_attachment(
+        &self,
+        attachment_id: usize,
+    ) -> Result<HomeworkAttachmentDelete, ErrorResponse> {
+        let params = new_params!("pupil_id", &self.student_id);
+
+        let text = self
+            .request(Method::POST, format!("/deletehomeworkattachment/{}", attachment_id), Some(params))
+            .await?;
+        let data: HomeworkAttachmentDelete = serde_json::from_str(&text)?;
+
+        return Ok(data);
+    }
 }
 
 #[cfg(test)]
@@ -223,10 +770,418 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
-        let _ = client.get_homeworks(None).await.unwrap();
+        let homeworks = client.get_homeworks(None).await.unwrap();
+
+        // responses can be cached and restored without losing anything
+        let cached = serde_json::to_value(&homeworks).unwrap();
+
+        assert_eq!(cached["data"][0]["status"]["ticked"], "yes");
+        assert_eq!(cached["data"][0]["due_date"], "2023-09-19");
+        assert_eq!(serde_json::from_value::<Homeworks>(cached).unwrap(), homeworks);
 
         homeworks_response.assert();
     }
+
+    fn homework_json(id: usize, subject: &str, ticked: &str) -> serde_json::Value {
+        return json!({
+            "lesson": "Lesson",
+            "subject": subject,
+            "teacher": "Teacher",
+            "homework_type": "Homework",
+            "id": id,
+            "title": "Homework",
+            "meta_title": "",
+            "description": "",
+            "issue_date": "2023-09-16",
+            "due_date": "2023-09-19",
+            "completion_time_unit": "minutes",
+            "completion_time_value": "",
+            "publish_time": "00:00:00",
+            "status": {
+                "id": id,
+                "state": null,
+                "mark": null,
+                "mark_relative": 0,
+                "ticked": ticked,
+                "allow_attachments": false,
+                "first_seen_date": null,
+                "last_seen_date": null,
+                "attachments": [],
+                "has_feedback": false
+            },
+            "validated_links": [],
+            "validated_attachments": []
+        });
+    }
+
+    #[tokio::test]
+    async fn query_homeworks_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let homeworks_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/homeworks/student_id")
+                .query_param("display_date", "issue_date")
+                .query_param("from", "2023-09-11")
+                .query_param("to", "2023-09-17");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [
+                        homework_json(1, "Maths", "no"),
+                        homework_json(2, "Maths", "yes"),
+                        homework_json(3, "English", "no")
+                    ],
+                    "meta": {
+                        "start_date": "2023-09-11",
+                        "end_date": "2023-09-17",
+                        "display_type": "issue_date",
+                        "max_files_allowed": 5,
+                        "allowed_file_types": [],
+                        "this_week_due_count": 0,
+                        "this_week_outstanding_count": 0,
+                        "this_week_completed_count": 0,
+                        "allow_attachments": true,
+                        "display_marks": false
+                    }
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let query = HomeworkQuery::new()
+            .issued_between(
+                NaiveDate::from_ymd_opt(2023, 9, 11).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 9, 17).unwrap(),
+            )
+            .outstanding()
+            .subject("maths");
+
+        let homeworks = client.query_homeworks(&query).await.unwrap();
+        let ids: Vec<usize> = homeworks.data.iter().map(|homework| homework.id).collect();
+
+        assert_eq!(ids, vec![1]);
+
+        homeworks_response.assert();
+    }
+
+    #[tokio::test]
+    async fn get_homework_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        let mut homework = homework_json(5488456, "Maths", "yes");
+        homework["status"]["mark"] = json!("9/10");
+        homework["status"]["has_feedback"] = json!(true);
+        homework["status"]["feedback"] = json!("Well done");
+        homework["status"]["attachments"] = json!([{
+            "id": 123,
+            "file_name": "essay.pdf",
+            "file": "https://example.com/essay.pdf",
+            "validated_file": "https://example.com/essay.pdf",
+            "teacher_note": "",
+            "teacher_file": null,
+            "validated_teacher_file": null
+        }]);
+
+        // Create a mock on the server.
+        let homework_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/homework/5488456")
+                .query_param("pupil_id", "student_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": homework,
+                    "meta": []
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let homework = client.get_homework(5488456).await.unwrap().data;
+
+        assert_eq!(homework.status.feedback(), Some("Well done"));
+        assert_eq!(homework.status.mark.percentage(), Some(90.0));
+        assert_eq!(homework.status.attachments[0].file_name, "essay.pdf");
+
+        homework_response.assert();
+    }
+
+    #[test]
+    fn homework_mark_test() {
+        let marks: Vec<HomeworkMark> =
+            serde_json::from_value(json!([null, "", 7, "8/10", "8 / 10", "85%", "A*", true]))
+                .unwrap();
+
+        assert_eq!(
+            marks,
+            vec![
+                HomeworkMark::None,
+                HomeworkMark::None,
+                HomeworkMark::Numeric { score: 7.0, out_of: None },
+                HomeworkMark::Numeric { score: 8.0, out_of: Some(10.0) },
+                HomeworkMark::Numeric { score: 8.0, out_of: Some(10.0) },
+                HomeworkMark::Percentage(85.0),
+                HomeworkMark::Grade("A*".to_string()),
+                HomeworkMark::Other(json!(true)),
+            ]
+        );
+
+        assert_eq!(marks[3].percentage(), Some(80.0));
+        assert_eq!(marks[5].percentage(), Some(85.0));
+        assert_eq!(marks[6].percentage(), None);
+        assert_eq!(marks[6].grade(), Some("A*"));
+        assert!(!marks[0].is_marked());
+
+        let serialized = serde_json::to_value(&marks).unwrap();
+
+        assert_eq!(serialized, json!([null, null, 7.0, "8/10", "8/10", "85%", "A*", true]));
+    }
+
+    #[test]
+    fn marks_by_subject_test() {
+        let mut maths = homework_json(1, "Maths", "yes");
+        maths["status"]["mark"] = json!("8/10");
+
+        let mut more_maths = homework_json(2, "Maths", "yes");
+        more_maths["status"]["mark"] = json!("60%");
+
+        let mut english = homework_json(5, "English", "yes");
+        english["status"]["mark"] = json!("B");
+        english["status"]["mark_relative"] = json!("65");
+
+        let homeworks: Vec<Homework> = serde_json::from_value(json!([
+            maths,
+            more_maths,
+            homework_json(3, "Maths", "no"),
+            homework_json(4, "English", "no"),
+            english,
+        ]))
+        .unwrap();
+
+        let subjects = marks_by_subject(&homeworks);
+
+        assert_eq!(subjects["Maths"].homework_count, 3);
+        assert_eq!(subjects["Maths"].marks.len(), 2);
+        assert_eq!(subjects["Maths"].average_percentage(), Some(70.0));
+        assert_eq!(subjects["Maths"].percentages, vec![80.0, 60.0]);
+        assert_eq!(subjects["English"].homework_count, 2);
+        assert_eq!(subjects["English"].average_percentage(), Some(65.0));
+    }
+
+    #[test]
+    fn due_in_week_of_test() {
+        let options = HomeworkQuery::new()
+            .due_in_week_of(NaiveDate::from_ymd_opt(2023, 9, 14).unwrap())
+            .options();
+
+        assert_eq!(options.display_date, Some(DisplayDate::DueDate));
+        assert_eq!(options.from, NaiveDate::from_ymd_opt(2023, 9, 11));
+        assert_eq!(options.to, NaiveDate::from_ymd_opt(2023, 9, 17));
+    }
+
+    #[test]
+    fn date_range_replaced_test() {
+        let date = NaiveDate::from_ymd_opt(2023, 9, 14).unwrap();
+
+        let options = HomeworkQuery::new()
+            .due_in_week_of(date)
+            .issued_since(date)
+            .options();
+
+        assert_eq!(options.display_date, Some(DisplayDate::IssueDate));
+        assert_eq!(options.from, Some(date));
+        assert_eq!(options.to, Some(uk_today()));
+    }
+
+    #[tokio::test]
+    async fn tick_homework_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let tick_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/homeworkticked/3459984")
+                .body("pupil_id=student_id&ticked=yes");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "id": 3459984,
+                        "state": "completed",
+                        "mark": null,
+                        "mark_relative": 0,
+                        "ticked": "yes",
+                        "allow_attachments": false,
+                        "allow_marking_completed": true,
+                        "first_seen_date": "2023-09-15T12:19:16+00:00",
+                        "last_seen_date": "2023-09-16T11:17:11+00:00",
+                        "attachments": [],
+                        "has_feedback": false
+                    },
+                    "meta": []
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let status = client.tick_homework(3459984, true).await.unwrap();
+
+        assert!(status.data.ticked);
+
+        tick_response.assert();
+    }
+
+    fn attachment_status(attachments: Vec<HomeworkAttachment>) -> HomeworkStatus {
+        return HomeworkStatus {
+            id: 3459984,
+            state: None,
+            mark: HomeworkMark::None,
+            mark_relative: Some(0.0),
+            ticked: false,
+            allow_attachments: true,
+            first_seen_date: None,
+            last_seen_date: None,
+            attachments,
+            has_feedback: false,
+            feedback: None,
+        };
+    }
+
+    fn attachment_meta() -> HomeworkMeta {
+        return HomeworkMeta {
+            start_date: NaiveDate::from_ymd_opt(2023, 9, 17).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2023, 10, 24).unwrap(),
+            display_type: DisplayDate::DueDate,
+            max_files_allowed: 1,
+            allowed_file_types: vec!["pdf".to_string(), "docx".to_string()],
+            this_week_due_count: 0,
+            this_week_outstanding_count: 0,
+            this_week_completed_count: 0,
+            allow_attachments: true,
+            display_marks: false,
+        };
+    }
+
+    #[tokio::test]
+    async fn upload_homework_attachment_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let upload_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/uploadhomeworkattachment/3459984")
+                .header_exists("content-type")
+                .body_contains("filename=\"essay.PDF\"")
+                .body_contains("essay contents");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "id": 123,
+                        "file_name": "essay.PDF",
+                        "file": "https://example.com/essay.PDF",
+                        "validated_file": "https://example.com/essay.PDF"
+                    },
+                    "meta": []
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let attachment = client
+            .upload_homework_attachment(
+                &attachment_status(vec![]),
+                &attachment_meta(),
+                "essay.PDF",
+                b"essay contents".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(attachment.data.id, 123);
+
+        upload_response.assert();
+    }
+
+    #[tokio::test]
+    async fn invalid_homework_attachment_test() {
+        let client = Client::generate_mock("http://localhost".to_string());
+        let meta = attachment_meta();
+
+        let wrong_type = client
+            .upload_homework_attachment(&attachment_status(vec![]), &meta, "virus.exe", vec![])
+            .await;
+
+        assert!(matches!(wrong_type, Err(ErrorResponse::InvalidAttachmentError(_))));
+
+        let existing = HomeworkAttachment {
+            id: 123,
+            file_name: "essay.pdf".to_string(),
+            file: "".to_string(),
+            validated_file: "".to_string(),
+            teacher_note: None,
+            teacher_file: None,
+            validated_teacher_file: None,
+        };
+
+        let too_many = client
+            .upload_homework_attachment(&attachment_status(vec![existing]), &meta, "essay.pdf", vec![])
+            .await;
+
+        assert!(matches!(too_many, Err(ErrorResponse::InvalidAttachmentError(_))));
+    }
+
+    #[test]
+    fn attachment_file_types_test() {
+        let status = attachment_status(vec![]);
+        let mut meta = attachment_meta();
+
+        meta.allowed_file_types = vec![".PDF".to_string()];
+
+        assert!(validate_attachment(&status, &meta, "essay.pdf").is_ok());
+        assert!(validate_attachment(&status, &meta, "essay.docx").is_err());
+
+        meta.allowed_file_types = vec![];
+
+        assert!(validate_attachment(&status, &meta, "essay.docx").is_ok());
+        assert!(validate_attachment(&status, &meta, "essay").is_ok());
+    }
+
+    #[tokio::test]
+    async fn delete_homework_attachment_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let delete_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/deletehomeworkattachment/123")
+                .body("pupil_id=student_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [],
+                    "meta": []
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        client.delete_homework_attachment(123).await.unwrap();
+
+        delete_response.assert();
+    }
 }
@@ -1,12 +1,18 @@
-use chrono::NaiveDate;
-use serde::Deserialize;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    client::{ErrorResponse, CCParser, SuccessResponse, Client},
+    api::dates::{
+        deserialize_date, deserialize_dates, deserialize_datetime, deserialize_time,
+        deserialize_wall_datetime,
+    },
+    api::student::Student,
+    client::{ErrorResponse, SuccessResponse, Client},
     new_params,
 };
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Lesson {
     pub teacher_name: String,
     pub lesson_name: String,
@@ -15,9 +21,12 @@ pub struct Lesson {
     pub period_name: String,
     pub period_number: String,
     pub room_name: String,
-    pub date: String,
-    pub start_time: String,
-    pub end_time: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub start_time: NaiveDateTime,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub end_time: NaiveDateTime,
     pub key: usize,
     pub note_abstract: String,
     pub note: String,
@@ -26,12 +35,44 @@ pub struct Lesson {
     pub pupil_note_raw: String,
 }
 
-#[derive(Deserialize, Debug)]
+impl Lesson {
+    /// The week of the school's timetable cycle the lesson is in, e.g. `2` for a `period_name` of
+    /// `2Tue:P1`, or `None` if the `period_name` doesn't start with one.
+    pub fn cycle_week(&self) -> Option<u32> {
+        let digits: String = self
+            .period_name
+            .chars()
+            .take_while(|char| char.is_ascii_digit())
+            .collect();
+
+        return digits.parse().ok();
+    }
+}
+
+/// A period of the school day.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Period {
+    pub number: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start_time: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end_time: NaiveTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LessonsMeta {
-    pub dates: Vec<String>,
-    pub timetable_dates: Vec<String>,
-    pub start_time: String,
-    pub end_time: String,
+    #[serde(deserialize_with = "deserialize_dates")]
+    pub dates: Vec<NaiveDate>,
+    #[serde(deserialize_with = "deserialize_dates")]
+    pub timetable_dates: Vec<NaiveDate>,
+    /// The start of the day, e.g. `2023-09-26 00:00:00`.
+    #[serde(deserialize_with = "deserialize_wall_datetime")]
+    pub start_time: NaiveDateTime,
+    /// The end of the day, e.g. `2023-09-26 23:59:59`.
+    #[serde(deserialize_with = "deserialize_wall_datetime")]
+    pub end_time: NaiveDateTime,
+    #[serde(default)]
+    pub periods: Vec<Period>,
 }
 
 pub type LessonsData = Vec<Lesson>;
@@ -47,25 +88,92 @@ impl Client {
     /// // Gets the student's lessons for the current day. 
     /// client.get_lessons(chrono::Utc::now().date());
     /// ```
-    pub async fn get_lessons(&mut self, date: NaiveDate) -> Result<Lessons, ErrorResponse> {
+    pub async fn get_lessons(&self, date: NaiveDate) -> Result<Lessons, ErrorResponse> {
         let params = new_params!("date", &date.format("%Y-%m-%d").to_string()); 
 
-        let request = self
-            .build_get(format!("/timetable/{}?{}", self.student_id, params))
-            .await?
-            .send()
+        let text = self
+            .request(Method::GET, format!("/timetable/{}?{}", self.student_id, params), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Lessons = serde_json::from_str(&text)?;
 
         return Ok(data);
     }
+
+    /// Sets the current student's own note on a lesson, replacing any note they already have.
+    /// The `lesson_key` is the lesson's `key`. Returns the lesson with the new note, where
+    /// `pupil_note` is the escaped text rather than the HTML ClassCharts would render.
+    ///
+    /// The `student` is from `get_student_info`, and a `LessonNotesDisabledError` is returned if
+    /// their school doesn't allow notes (`allow_pupils_add_timetable_notes`). An
+    /// `UnknownLessonError` is returned, without changing anything, if there is no lesson with
+    /// the `lesson_key` on the `date`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let student = client.get_student_info().await?.data.user;
+    /// let lesson = &client.get_lessons(date).await?.data[0];
+    /// let lesson = client
+    ///     .set_lesson_note(&student, lesson.key, lesson.date, "Bring a calculator")
+    ///     .await?;
+    /// ```
+    pub async fn set_lesson_note(
+        &self,
+        student: &Student,
+        lesson_key: usize,
+        date: NaiveDate,
+        text: &str,
+    ) -> Result<Lesson, ErrorResponse> {
+        if !student.allow_pupils_add_timetable_notes {
+            return Err(ErrorResponse::LessonNotesDisabledError);
+        }
+
+        let mut lesson = self
+            .get_lessons(date)
+            .await?
+            .data
+            .into_iter()
+            .find(|lesson| lesson.key == lesson_key)
+            .ok_or(ErrorResponse::UnknownLessonError(lesson_key))?;
+
+        let params = new_params!(
+            "lesson_key", &lesson_key.to_string(),
+            "date", &date.format("%Y-%m-%d").to_string(),
+            "note", text
+        );
+
+        self.request(Method::POST, format!("/timetablenote/{}", self.student_id), Some(params))
+            .await?;
+
+        lesson.pupil_note_raw = text.to_string();
+        lesson.pupil_note_abstract = text.to_string();
+        lesson.pupil_note = escape_html(text);
+
+        return Ok(lesson);
+    }
+
+    /// Removes the current student's own note from a lesson. See `Client::set_lesson_note`.
+    pub async fn clear_lesson_note(
+        &self,
+        student: &Student,
+        lesson_key: usize,
+        date: NaiveDate,
+    ) -> Result<Lesson, ErrorResponse> {
+        return self.set_lesson_note(student, lesson_key, date, "").await;
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::student::StudentInfo;
     use httpmock::prelude::*;
     use serde_json::json;
 
@@ -285,13 +393,168 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
-        let _ = client
+        let lessons = client
             .get_lessons(NaiveDate::from_ymd_opt(2023, 9, 26).unwrap())
             .await
             .unwrap();
 
+        let date = NaiveDate::from_ymd_opt(2023, 9, 26).unwrap();
+
+        assert_eq!(lessons.data[0].date, date);
+        assert_eq!(lessons.data[0].start_time, date.and_hms_opt(8, 45, 0).unwrap());
+        assert_eq!(lessons.meta.dates, vec![date]);
+        assert_eq!(lessons.meta.start_time, date.and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(lessons.meta.end_time, date.and_hms_opt(23, 59, 59).unwrap());
+        assert_eq!(lessons.meta.periods[3].number, "P3");
+        assert_eq!(lessons.meta.periods[3].start_time, NaiveTime::from_hms_opt(11, 20, 0).unwrap());
+        assert_eq!(lessons.data[0].cycle_week(), Some(2));
+
         lessons_response.assert();
     }
+
+    fn student_json(allow_pupils_add_timetable_notes: bool) -> serde_json::Value {
+        let mut user = json!({
+            "id": 3949234,
+            "name": "Name",
+            "first_name": "first_name",
+            "last_name": "last_name",
+            "avatar_url": "https://example.com",
+            "display_attendance_type": "instance",
+            "can_upload_attachments": false,
+            "pupil_concerns_help_text": "",
+            "allow_pupils_add_timetable_notes": allow_pupils_add_timetable_notes,
+            "detention_alias_plural_uc": "Detentions",
+            "announcements_count": 0,
+            "messages_count": 0,
+            "pusher_channel_name": "pusher_channel_name",
+            "survey_id": null
+        });
+
+        for flag in [
+            "display_behaviour", "display_parent_behaviour", "display_homework", "display_rewards",
+            "display_detentions", "display_report_cards", "display_classes", "display_announcements",
+            "display_attendance", "display_attendance_percentage", "display_activity",
+            "display_mental_health", "display_timetable", "is_disabled",
+            "display_two_way_communications", "display_absences", "display_event_badges",
+            "display_avatars", "display_concern_submission", "display_custom_fields",
+            "has_birthday", "has_new_survey",
+        ] {
+            user[flag] = json!(false);
+        }
+
+        return json!({
+            "success": 1,
+            "data": { "user": user },
+            "meta": { "version": "27.16.2" }
+        });
+    }
+
+    fn student(allow_pupils_add_timetable_notes: bool) -> Student {
+        let info: StudentInfo =
+            serde_json::from_value(student_json(allow_pupils_add_timetable_notes)).unwrap();
+
+        return info.data.user;
+    }
+
+    #[tokio::test]
+    async fn set_lesson_note_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let note_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/timetablenote/student_id")
+                .body_contains("lesson_key=349593459&date=2023-09-26&note=Bring+a+calculator");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({ "success": 1, "data": [], "meta": [] }));
+        });
+
+        let lessons_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/timetable/student_id")
+                .query_param("date", "2023-09-26");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [
+                        {
+                            "teacher_name": "Teacher Name",
+                            "lesson_name": "Lesson Name",
+                            "subject_name": "Subject Name",
+                            "is_alternative_lesson": false,
+                            "period_name": "2Tue:P1",
+                            "period_number": "P1",
+                            "room_name": "Room Name",
+                            "date": "2023-09-26",
+                            "start_time": "2023-09-26T08:45:00+01:00",
+                            "end_time": "2023-09-26T09:45:00+01:00",
+                            "key": 349593459,
+                            "note_abstract": "",
+                            "note": "",
+                            "pupil_note_abstract": "",
+                            "pupil_note": "",
+                            "pupil_note_raw": ""
+                        }
+                    ],
+                    "meta": {
+                        "dates": ["2023-09-26"],
+                        "timetable_dates": ["2023-09-26"],
+                        "start_time": "2023-09-26T00:00:00+00:00",
+                        "end_time": "2023-09-26T23:59:59+00:00"
+                    }
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+        let student = student(true);
+        let date = NaiveDate::from_ymd_opt(2023, 9, 26).unwrap();
+
+        let lesson = client
+            .set_lesson_note(&student, 349593459, date, "Bring a calculator")
+            .await
+            .unwrap();
+
+        assert_eq!(lesson.pupil_note_raw, "Bring a calculator");
+        assert_eq!(lesson.pupil_note, "Bring a calculator");
+
+        let error = client.set_lesson_note(&student, 1, date, "Bring a calculator").await;
+
+        assert!(matches!(error, Err(ErrorResponse::UnknownLessonError(1))));
+
+        note_response.assert_hits(1);
+        lessons_response.assert_hits(2);
+
+        assert_eq!(escape_html("<b>Tom & Jerry</b>"), "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;");
+    }
+
+    #[tokio::test]
+    async fn set_lesson_note_disabled_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let note_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/timetablenote/student_id");
+            then.status(200);
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let error = client
+            .clear_lesson_note(
+                &student(false),
+                349593459,
+                NaiveDate::from_ymd_opt(2023, 9, 26).unwrap(),
+            )
+            .await;
+
+        assert!(matches!(error, Err(ErrorResponse::LessonNotesDisabledError)));
+
+        note_response.assert_hits(0);
+    }
 }
@@ -1,19 +1,25 @@
 pub mod activity;
 pub mod announcements;
+pub mod attachments;
 pub mod attendance;
 pub mod badges;
 pub mod behaviour;
+pub mod dates;
 pub mod detentions;
 mod helpers;
 pub mod homework;
 pub mod lessons;
 pub mod pupilfields;
+pub mod pupils;
 pub mod rewards;
 pub mod student;
+pub mod timetable;
 
 #[cfg(test)]
 mod tests {
-    use crate::Client;
+    use std::sync::Arc;
+
+    use crate::{client::PARENT_API_PATH, Client, ParentClient, ReqwestTransport, RetryPolicy};
 
     impl Client {
         pub fn generate_mock(base_url: String) -> Client {
@@ -25,4 +31,22 @@ mod tests {
             );
         }
     }
+
+    impl ParentClient {
+        pub fn generate_mock(base_url: String) -> ParentClient {
+            return ParentClient::from_client(
+                Client::from_parts(
+                    Arc::new(ReqwestTransport::default()),
+                    "student_id".to_string(),
+                    base_url,
+                    PARENT_API_PATH.to_string(),
+                    "auth_cookies".to_string(),
+                    "session_id".to_string(),
+                    RetryPolicy::default(),
+                    None,
+                ),
+                vec![],
+            );
+        }
+    }
 }
@@ -1,10 +1,11 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
+use crate::client::{ErrorResponse, SuccessResponse, Client};
 
 use super::helpers::Empty;
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PupilField {
     pub id: usize,
     pub name: String,
@@ -12,7 +13,7 @@ pub struct PupilField {
     pub value: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PupilFieldsData {
     pub note: String,
     pub fields: Vec<PupilField>,
@@ -24,14 +25,10 @@ pub type PupilFields = SuccessResponse<PupilFieldsData, PupilFieldsMeta>;
 
 impl Client {
     /// Gets the current student's pupil fields. 
-    pub async fn get_pupilfields(&mut self) -> Result<PupilFields, ErrorResponse> {
-        let request = self
-            .build_get(format!("/customfields/{}", self.student_id))
-            .await?
-            .send()
+    pub async fn get_pupilfields(&self) -> Result<PupilFields, ErrorResponse> {
+        let text = self
+            .request(Method::GET, format!("/customfields/{}", self.student_id), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: PupilFields = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -72,7 +69,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_pupilfields().await.unwrap();
 
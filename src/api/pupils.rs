@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use reqwest::Method;
+
+use crate::client::{ErrorResponse, SuccessResponse};
+use crate::parent::ParentClient;
+
+use super::helpers::Empty;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pupil {
+    pub id: usize,
+    pub name: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_url: String,
+    pub school_name: String,
+    pub school_logo: Option<String>,
+    pub timezone: String,
+    pub display_behaviour: bool,
+    pub display_homework: bool,
+    pub display_rewards: bool,
+    pub display_detentions: bool,
+    pub display_announcements: bool,
+    pub display_attendance: bool,
+    pub display_timetable: bool,
+    pub announcements_count: usize,
+    pub messages_count: usize,
+    pub detention_yes_count: usize,
+    pub detention_no_count: usize,
+    pub detention_pending_count: usize,
+    pub detention_upscaled_count: usize,
+    pub homework_todo_count: usize,
+    pub homework_late_count: usize,
+    pub homework_not_completed_count: usize,
+    pub homework_excused_count: usize,
+    pub homework_completed_count: usize,
+    pub homework_submitted_count: usize,
+}
+
+pub type PupilsData = Vec<Pupil>;
+pub type PupilsMeta = Vec<Empty>;
+pub type Pupils = SuccessResponse<PupilsData, PupilsMeta>;
+
+impl ParentClient {
+    /// Gets the pupils linked to the current parent account.
+    /// This does not update `pupils` on the client.
+    pub async fn get_pupils(&self) -> Result<Pupils, ErrorResponse> {
+        let text = self
+            .client()
+            .request(Method::GET, "/pupils".to_string(), None)
+            .await?;
+        let data: Pupils = serde_json::from_str(&text)?;
+
+        return Ok(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn get_pupils_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let pupils_response = server.mock(|when, then| {
+            when.method(GET).path("/apiv2parent/pupils");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [
+                        {
+                            "id": 3949234,
+                            "name": "Name",
+                            "first_name": "first_name",
+                            "last_name": "last_name",
+                            "avatar_url": "https://example.com",
+                            "school_name": "School Name",
+                            "school_logo": null,
+                            "timezone": "Europe/London",
+                            "display_behaviour": true,
+                            "display_homework": true,
+                            "display_rewards": false,
+                            "display_detentions": true,
+                            "display_announcements": true,
+                            "display_attendance": true,
+                            "display_timetable": true,
+                            "announcements_count": 0,
+                            "messages_count": 0,
+                            "detention_yes_count": 1,
+                            "detention_no_count": 0,
+                            "detention_pending_count": 0,
+                            "detention_upscaled_count": 0,
+                            "homework_todo_count": 2,
+                            "homework_late_count": 0,
+                            "homework_not_completed_count": 0,
+                            "homework_excused_count": 0,
+                            "homework_completed_count": 5,
+                            "homework_submitted_count": 0
+                        }
+                    ],
+                    "meta": []
+                }));
+        });
+
+        let client = ParentClient::generate_mock(server.base_url());
+
+        let pupils = client.get_pupils().await.unwrap();
+
+        assert_eq!(pupils.data.len(), 1);
+
+        pupils_response.assert();
+    }
+}
@@ -1,15 +1,55 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::api::helpers::deserialize_yes_no_bool;
-use crate::client::{ErrorResponse, CCParser, SuccessResponse, Client};
+use crate::api::helpers::{deserialize_yes_no_bool, serialize_yes_no_bool, WireNumber};
+use crate::client::{ErrorResponse, SuccessResponse, Client};
+use crate::new_params;
 
 use super::helpers::Empty;
+use reqwest::Method;
+
+/// How many times a reward has been purchased. ClassCharts sends this as a number or a string,
+/// and it is serialized the same way.
+#[derive(Debug, Clone, Default)]
+pub struct PurchasedCount {
+    count: u32,
+    wire: WireNumber,
+}
+
+impl PurchasedCount {
+    pub fn new(count: u32) -> PurchasedCount {
+        return PurchasedCount {
+            count,
+            wire: WireNumber::Number,
+        };
+    }
+
+    pub fn count(&self) -> u32 {
+        return self.count;
+    }
+}
+
+impl PartialEq for PurchasedCount {
+    fn eq(&self, other: &Self) -> bool {
+        return self.count == other.count;
+    }
+}
+
+impl Eq for PurchasedCount {}
+
+impl From<PurchasedCount> for u32 {
+    fn from(purchased_count: PurchasedCount) -> Self {
+        return purchased_count.count;
+    }
+}
 
-#[derive(Debug)]
-pub enum PurchasedCount {
-    String(String),
-    Number(usize),
+impl Serialize for PurchasedCount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        return self.wire.serialize(Some(self.count), serializer);
+    }
 }
 
 impl<'de> Deserialize<'de> for PurchasedCount {
@@ -18,20 +58,17 @@ impl<'de> Deserialize<'de> for PurchasedCount {
         D: serde::Deserializer<'de>,
     {
         let value: Value = Deserialize::deserialize(deserializer)?;
+        let (count, wire) = WireNumber::parse(value.clone(), "purchased_count")
+            .map_err(serde::de::Error::custom)?;
+        let count = count.ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid purchased_count: {}", value))
+        })?;
 
-        match value {
-            Value::String(s) => Ok(PurchasedCount::String(s)),
-            Value::Number(num) if num.is_u64() => {
-                Ok(PurchasedCount::Number(num.as_u64().unwrap() as usize))
-            }
-            _ => Err(serde::de::Error::custom(
-                "Invalid format for 'purchased_count' field",
-            )),
-        }
+        return Ok(PurchasedCount { count, wire });
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RewardItem {
     pub id: usize,
     pub name: String,
@@ -48,7 +85,7 @@ pub struct RewardItem {
     pub price_balance_difference: usize,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RewardsMeta {
     pub pupil_score_balance: usize,
 }
@@ -57,9 +94,12 @@ pub type RewardsData = Vec<RewardItem>;
 
 pub type Rewards = SuccessResponse<RewardsData, RewardsMeta>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RewardPurchaseData {
-    #[serde(deserialize_with = "deserialize_yes_no_bool")]
+    #[serde(
+        deserialize_with = "deserialize_yes_no_bool",
+        serialize_with = "serialize_yes_no_bool"
+    )]
     pub single_purchase: bool,
     pub order_id: usize,
     pub balance: usize,
@@ -71,14 +111,10 @@ pub type RewardPurchase = SuccessResponse<RewardPurchaseData, RewardPurchaseMeta
 
 impl Client {
     /// Gets the available items in the current student's rewards shop.
-    pub async fn get_rewards(&mut self) -> Result<Rewards, ErrorResponse> {
-        let request = self
-            .build_get(format!("/rewards/{}", self.student_id))
-            .await?
-            .send()
+    pub async fn get_rewards(&self) -> Result<Rewards, ErrorResponse> {
+        let text = self
+            .request(Method::GET, format!("/rewards/{}", self.student_id), None)
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: Rewards = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -86,30 +122,31 @@ impl Client {
 
     /// Purchase a reward item from the current student's rewards shop.
     /// The `item_id` should be an integer and can be grabbed from the `get_rewards` method.
+    /// This is never retried automatically, as a retry could buy the item twice.
     pub async fn purchase_reward<T>(
-        &mut self,
+        &self,
         item_id: T,
     ) -> Result<RewardPurchase, ErrorResponse>
     where
         T: std::fmt::Display,
     {
-        let request = self
-            .build_post(format!("/purchase/{}", item_id))
-            .await?
-            .body(format!("pupil_id={}", self.student_id))
-            .send()
-            .await?;
-
-        let text = request.cc_parse().await.map_err(|err| {
-            if let ErrorResponse::SerdeJsonParsingError(_err) = err {
-                return ErrorResponse::ClassChartsError(
-                    0,
-                    "Internal Server Error, the item may not exist.".to_string(),
-                );
-            } else {
-                return err;
-            }
-        })?;
+        let params = new_params!("pupil_id", &self.student_id);
+
+        let text = self
+            .request_once(Method::POST, format!("/purchase/{}", item_id), Some(params))
+            .await
+            .map_err(|err| {
+                if let ErrorResponse::SerdeJsonParsingError(_)
+                | ErrorResponse::HttpStatusError(reqwest::StatusCode::INTERNAL_SERVER_ERROR) = err
+                {
+                    return ErrorResponse::ClassChartsError(
+                        0,
+                        "Internal Server Error, the item may not exist.".to_string(),
+                    );
+                } else {
+                    return err;
+                }
+            })?;
         let data: RewardPurchase = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -143,7 +180,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.purchase_reward("item_id").await.unwrap();
 
@@ -185,10 +222,28 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
+
+        let rewards = client.get_rewards().await.unwrap();
+
+        let cached = serde_json::to_string(&rewards).unwrap();
 
-        let _ = client.get_rewards().await.unwrap();
+        assert_eq!(serde_json::from_str::<Rewards>(&cached).unwrap(), rewards);
+        assert_eq!(rewards.data[0].purchased_count.count(), 10);
+        assert!(cached.contains("\"purchased_count\":\"10\""));
 
         rewards_response.assert();
     }
+
+    #[test]
+    fn purchased_count_test() {
+        let purchased_count = |value: Value| serde_json::from_value::<PurchasedCount>(value);
+
+        assert_eq!(purchased_count(json!(3)).unwrap(), PurchasedCount::new(3));
+        assert_eq!(purchased_count(json!(" 3")).unwrap().count(), 3);
+        assert!(purchased_count(json!("")).is_err());
+        assert!(purchased_count(json!("lots")).is_err());
+        assert!(purchased_count(json!(null)).is_err());
+        assert_eq!(serde_json::to_value(purchased_count(json!(3)).unwrap()).unwrap(), json!(3));
+    }
 }
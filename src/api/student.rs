@@ -1,10 +1,11 @@
 use crate::{
-    client::{ErrorResponse, CCParser, SuccessResponse, Client},
+    client::{ErrorResponse, SuccessResponse, Client},
     new_params,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use reqwest::Method;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Student {
     pub id: usize,
     pub name: String,
@@ -44,12 +45,12 @@ pub struct Student {
     pub detention_alias_plural_uc: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StudentInfoData {
     pub user: Student,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StudentInfoMeta {
     pub version: String,
 }
@@ -58,21 +59,12 @@ pub type StudentInfo = SuccessResponse<StudentInfoData, StudentInfoMeta>;
 
 impl Client {
     /// Gets general information about the current student.
-    pub async fn get_student_info(&mut self) -> Result<StudentInfo, ErrorResponse> {
+    pub async fn get_student_info(&self) -> Result<StudentInfo, ErrorResponse> {
         let params = new_params!("include_data", "true");
 
-        let request = self
-            .build_post("/ping")
-            .await?
-            .header(
-                reqwest::header::CONTENT_TYPE,
-                "application/x-www-form-urlencoded",
-            )
-            .body(params)
-            .send()
+        let text = self
+            .request(Method::POST, "/ping".to_string(), Some(params))
             .await?;
-
-        let text = request.cc_parse().await?;
         let data: StudentInfo = serde_json::from_str(&text)?;
 
         return Ok(data);
@@ -146,7 +138,7 @@ mod tests {
                 }));
         });
 
-        let mut client = Client::generate_mock(server.base_url());
+        let client = Client::generate_mock(server.base_url());
 
         let _ = client.get_student_info().await.unwrap();
 
@@ -0,0 +1,535 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::lessons::{Lesson, Lessons, Period},
+    client::{Client, ErrorResponse},
+};
+
+/// How many days `get_timetable` fetches at once.
+const CONCURRENT_REQUESTS: usize = 4;
+
+/// The lessons for a range of dates, from `Client::get_timetable`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Timetable {
+    /// The lessons for every timetable day which was fetched.
+    pub days: BTreeMap<NaiveDate, Lessons>,
+}
+
+impl Timetable {
+    /// The lessons for a date, if it was fetched.
+    pub fn day(&self, date: NaiveDate) -> Option<&Lessons> {
+        return self.days.get(&date);
+    }
+
+    /// The lesson in a period, e.g. `P1`, on a date.
+    pub fn lesson(&self, date: NaiveDate, period_number: &str) -> Option<&Lesson> {
+        return self
+            .day(date)?
+            .data
+            .iter()
+            .find(|lesson| lesson.period_number == period_number);
+    }
+
+    /// The lessons for a date as a `DayTimetable`, if it was fetched.
+    pub fn day_timetable(&self, date: NaiveDate) -> Option<DayTimetable> {
+        return self.day(date).cloned().map(DayTimetable::new);
+    }
+
+    /// Every lesson, in date order.
+    pub fn lessons(&self) -> impl Iterator<Item = &Lesson> {
+        return self.days.values().flat_map(|day| day.data.iter());
+    }
+
+    /// The school's repeating week cycle (e.g. week A / week B), detected from the `period_name`
+    /// of each lesson. Returns `None` if no lesson has a cycle week.
+    ///
+    /// The length of the cycle is the highest week seen, so the timetable should cover at least a
+    /// whole cycle for it to be correct.
+    pub fn cycle(&self) -> Option<WeekCycle> {
+        let mut weeks = BTreeMap::new();
+
+        for lesson in self.lessons() {
+            if let Some(week) = lesson.cycle_week() {
+                weeks.entry(week_start(lesson.date)).or_insert(week);
+            }
+        }
+
+        let length = *weeks.values().max()?;
+
+        return Some(WeekCycle { length, weeks });
+    }
+
+    /// The lessons expected on a date. If the date wasn't fetched, these are the lessons of the
+    /// latest fetched day on the same weekday, in the same week of the cycle.
+    ///
+    /// The lessons are returned as ClassCharts returned them, so keep their original `date`.
+    pub fn expected_lessons(&self, date: NaiveDate) -> Vec<&Lesson> {
+        if let Some(day) = self.day(date) {
+            return day.data.iter().collect();
+        }
+
+        let cycle = self.cycle();
+        let week = cycle.as_ref().map(|cycle| cycle.week_of(date));
+
+        let day = self.days.iter().rev().find(|(day, _)| {
+            return day.weekday() == date.weekday()
+                && cycle.as_ref().map(|cycle| cycle.week_of(**day)) == week;
+        });
+
+        return match day {
+            Some((_, day)) => day.data.iter().collect(),
+            None => vec![],
+        };
+    }
+}
+
+/// A repeating cycle of timetable weeks, e.g. a two week cycle of week A and week B.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeekCycle {
+    /// The number of weeks in the cycle.
+    pub length: u32,
+    /// The week of the cycle, starting from `1`, for the monday of every fetched week.
+    pub weeks: BTreeMap<NaiveDate, u32>,
+}
+
+impl WeekCycle {
+    /// The week of the cycle, starting from `1`, a date is in.
+    ///
+    /// Dates in weeks which weren't fetched are counted on from the closest fetched week, so may
+    /// be wrong if the cycle is paused over the holidays.
+    pub fn week_of(&self, date: NaiveDate) -> u32 {
+        let monday = week_start(date);
+
+        let known = self
+            .weeks
+            .range(..=monday)
+            .next_back()
+            .or_else(|| self.weeks.iter().next());
+
+        let Some((known_monday, known_week)) = known else {
+            return 1;
+        };
+
+        let weeks_since = (monday - *known_monday).num_weeks();
+        let length = i64::from(self.length.max(1));
+
+        return ((i64::from(*known_week) - 1 + weeks_since).rem_euclid(length) + 1) as u32;
+    }
+
+    /// The letter of the week of the cycle a date is in, e.g. `A` for week `1` and `B` for week `2`.
+    pub fn label_of(&self, date: NaiveDate) -> char {
+        return char::from_u32('A' as u32 + self.week_of(date) - 1).unwrap_or('?');
+    }
+}
+
+/// A single day's lessons, in order, for answering "what's now, what's next and where".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DayTimetable {
+    pub date: NaiveDate,
+    /// The lessons, ordered by their `start_time`.
+    pub lessons: Vec<Lesson>,
+    /// The periods of the school day, which may include some without a lesson.
+    pub periods: Vec<Period>,
+    /// The start of the day, from `LessonsMeta.start_time`.
+    pub start_time: NaiveDateTime,
+    /// The end of the day, from `LessonsMeta.end_time`.
+    pub end_time: NaiveDateTime,
+}
+
+/// Time between two consecutive lessons, like a break or lunch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+}
+
+impl Gap {
+    pub fn duration(&self) -> Duration {
+        return self.end_time - self.start_time;
+    }
+}
+
+/// Two consecutive lessons in different rooms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoomChange<'a> {
+    pub from: &'a Lesson,
+    pub to: &'a Lesson,
+}
+
+impl DayTimetable {
+    pub fn new(lessons: Lessons) -> DayTimetable {
+        let mut data = lessons.data;
+        data.sort_by_key(|lesson| lesson.start_time);
+
+        let date = lessons
+            .meta
+            .dates
+            .first()
+            .copied()
+            .unwrap_or(lessons.meta.start_time.date());
+
+        return DayTimetable {
+            date,
+            lessons: data,
+            periods: lessons.meta.periods,
+            start_time: lessons.meta.start_time,
+            end_time: lessons.meta.end_time,
+        };
+    }
+
+    fn includes(&self, at: NaiveDateTime) -> bool {
+        return self.start_time <= at && at <= self.end_time;
+    }
+
+    /// The lesson happening at a time.
+    pub fn current_lesson(&self, at: NaiveDateTime) -> Option<&Lesson> {
+        if !self.includes(at) {
+            return None;
+        }
+
+        return self
+            .lessons
+            .iter()
+            .find(|lesson| lesson.start_time <= at && at < lesson.end_time);
+    }
+
+    /// The next lesson to start after a time, or `None` if there are no more lessons that day.
+    pub fn next_lesson(&self, at: NaiveDateTime) -> Option<&Lesson> {
+        if at > self.end_time {
+            return None;
+        }
+
+        return self.lessons.iter().find(|lesson| lesson.start_time > at);
+    }
+
+    /// The periods without a lesson.
+    pub fn free_periods(&self) -> Vec<&Period> {
+        return self
+            .periods
+            .iter()
+            .filter(|period| {
+                return !self
+                    .lessons
+                    .iter()
+                    .any(|lesson| lesson.period_number == period.number);
+            })
+            .collect();
+    }
+
+    /// The time between consecutive lessons which don't follow straight on from each other.
+    pub fn gaps_between_lessons(&self) -> Vec<Gap> {
+        return self
+            .lessons
+            .windows(2)
+            .filter(|lessons| lessons[0].end_time < lessons[1].start_time)
+            .map(|lessons| Gap {
+                start_time: lessons[0].end_time,
+                end_time: lessons[1].start_time,
+            })
+            .collect();
+    }
+
+    /// Consecutive lessons which are in different rooms. Lessons without a room are ignored.
+    pub fn room_changes(&self) -> Vec<RoomChange<'_>> {
+        let lessons: Vec<&Lesson> = self
+            .lessons
+            .iter()
+            .filter(|lesson| !lesson.room_name.trim().is_empty())
+            .collect();
+
+        return lessons
+            .windows(2)
+            .filter(|lessons| lessons[0].room_name != lessons[1].room_name)
+            .map(|lessons| RoomChange {
+                from: lessons[0],
+                to: lessons[1],
+            })
+            .collect();
+    }
+}
+
+impl From<Lessons> for DayTimetable {
+    fn from(lessons: Lessons) -> DayTimetable {
+        return DayTimetable::new(lessons);
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    return date - Days::new(u64::from(date.weekday().num_days_from_monday()));
+}
+
+impl Client {
+    /// Gets the current student's lessons between two dates (inclusive).
+    /// Days are fetched concurrently, and days ClassCharts doesn't list in `timetable_dates` are
+    /// skipped.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let today = chrono::Local::now().date_naive();
+    /// let timetable = client.get_timetable(today, today + chrono::Days::new(13)).await?;
+    ///
+    /// if let Some(cycle) = timetable.cycle() {
+    ///     println!("This is week {}", cycle.label_of(today));
+    /// }
+    /// ```
+    pub async fn get_timetable(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Timetable, ErrorResponse> {
+        let mut timetable = Timetable::default();
+
+        if to < from {
+            return Ok(timetable);
+        }
+
+        // the first day is fetched on its own, for the `timetable_dates`
+        let first = self.get_lessons(from).await?;
+
+        let timetable_dates: BTreeSet<NaiveDate> =
+            first.meta.timetable_dates.iter().copied().collect();
+
+        let is_timetable_date = |date: &NaiveDate| {
+            let known_range = timetable_dates
+                .first()
+                .zip(timetable_dates.last())
+                .is_some_and(|(first, last)| first <= date && date <= last);
+
+            return !known_range || timetable_dates.contains(date);
+        };
+
+        if is_timetable_date(&from) {
+            timetable.days.insert(from, first);
+        }
+
+        let dates: Vec<NaiveDate> = from
+            .iter_days()
+            .skip(1)
+            .take_while(|date| *date <= to)
+            .filter(is_timetable_date)
+            .collect();
+
+        let days: Vec<Lessons> = stream::iter(dates.iter().map(|date| self.get_lessons(*date)))
+            .buffered(CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+        timetable.days.extend(dates.into_iter().zip(days));
+
+        return Ok(timetable);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn date(date: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+    }
+
+    fn lessons_json(date: &str, week: u32, subject: &str, timetable_dates: &[&str]) -> serde_json::Value {
+        let weekday = self::date(date).weekday();
+
+        return json!({
+            "success": 1,
+            "data": [
+                {
+                    "teacher_name": "Teacher Name",
+                    "lesson_name": "Lesson Name",
+                    "subject_name": subject,
+                    "is_alternative_lesson": false,
+                    "period_name": format!("{}{}:P1", week, weekday),
+                    "period_number": "P1",
+                    "room_name": "Room Name",
+                    "date": date,
+                    "start_time": format!("{}T08:45:00+01:00", date),
+                    "end_time": format!("{}T09:45:00+01:00", date),
+                    "key": 1,
+                    "note_abstract": "",
+                    "note": "",
+                    "pupil_note_abstract": "",
+                    "pupil_note": "",
+                    "pupil_note_raw": ""
+                }
+            ],
+            "meta": {
+                "dates": [date],
+                "timetable_dates": timetable_dates,
+                "start_time": format!("{}T00:00:00+01:00", date),
+                "end_time": format!("{}T23:59:59+01:00", date)
+            }
+        });
+    }
+
+    fn timetable(days: &[(&str, u32, &str)]) -> Timetable {
+        let days = days
+            .iter()
+            .map(|(day, week, subject)| {
+                let lessons = serde_json::from_value(lessons_json(day, *week, subject, &[])).unwrap();
+                return (date(day), lessons);
+            })
+            .collect();
+
+        return Timetable { days };
+    }
+
+    #[test]
+    fn cycle_test() {
+        let timetable = timetable(&[
+            ("2023-09-18", 1, "Maths"),
+            ("2023-09-25", 2, "English"),
+            ("2023-09-26", 2, "Science"),
+        ]);
+
+        let cycle = timetable.cycle().unwrap();
+
+        assert_eq!(cycle.length, 2);
+        assert_eq!(cycle.week_of(date("2023-09-20")), 1);
+        assert_eq!(cycle.week_of(date("2023-10-01")), 2);
+        assert_eq!(cycle.label_of(date("2023-10-02")), 'A');
+        assert_eq!(cycle.label_of(date("2023-10-09")), 'B');
+        assert_eq!(cycle.week_of(date("2023-09-11")), 2);
+
+        assert_eq!(timetable.lesson(date("2023-09-26"), "P1").unwrap().subject_name, "Science");
+        assert!(timetable.lesson(date("2023-09-26"), "P2").is_none());
+
+        // next week is week A, so has the same lessons as the 18th
+        let expected = timetable.expected_lessons(date("2023-10-02"));
+        assert_eq!(expected[0].subject_name, "Maths");
+
+        let expected = timetable.expected_lessons(date("2023-10-09"));
+        assert_eq!(expected[0].subject_name, "English");
+
+        assert!(timetable.expected_lessons(date("2023-10-04")).is_empty());
+    }
+
+    #[test]
+    fn day_timetable_test() {
+        let lesson = |period: &str, start: &str, end: &str, room: &str| {
+            return json!({
+                "teacher_name": "Teacher Name",
+                "lesson_name": "Lesson Name",
+                "subject_name": period,
+                "is_alternative_lesson": false,
+                "period_name": format!("1Tue:{}", period),
+                "period_number": period,
+                "room_name": room,
+                "date": "2023-09-26",
+                "start_time": format!("2023-09-26T{}:00+01:00", start),
+                "end_time": format!("2023-09-26T{}:00+01:00", end),
+                "key": 1,
+                "note_abstract": "",
+                "note": "",
+                "pupil_note_abstract": "",
+                "pupil_note": "",
+                "pupil_note_raw": ""
+            });
+        };
+
+        let period = |number: &str, start: &str, end: &str| {
+            return json!({ "number": number, "start_time": start, "end_time": end });
+        };
+
+        let lessons: Lessons = serde_json::from_value(json!({
+            "success": 1,
+            "data": [
+                lesson("P3", "11:20", "12:20", "S2"),
+                lesson("P1", "08:45", "09:45", "M4"),
+                lesson("P2", "09:45", "10:45", "M4"),
+            ],
+            "meta": {
+                "dates": ["2023-09-26"],
+                "timetable_dates": ["2023-09-26"],
+                "periods": [
+                    period("P1", "08:45:00", "09:45:00"),
+                    period("P2", "09:45:00", "10:45:00"),
+                    period("P3", "11:20:00", "12:20:00"),
+                    period("P4", "13:05:00", "14:05:00"),
+                ],
+                "start_time": "2023-09-26T00:00:00+00:00",
+                "end_time": "2023-09-26T23:59:59+00:00"
+            }
+        }))
+        .unwrap();
+
+        let day = DayTimetable::from(lessons);
+        let at = |time: &str| date("2023-09-26").and_time(time.parse().unwrap());
+
+        assert_eq!(day.date, date("2023-09-26"));
+        assert_eq!(day.start_time, at("00:00:00"));
+        assert_eq!(day.end_time, at("23:59:59"));
+        assert_eq!(day.lessons[0].period_number, "P1");
+
+        assert_eq!(day.current_lesson(at("09:00:00")).unwrap().period_number, "P1");
+        assert_eq!(day.current_lesson(at("09:45:00")).unwrap().period_number, "P2");
+        assert!(day.current_lesson(at("11:00:00")).is_none());
+
+        assert_eq!(day.next_lesson(at("09:00:00")).unwrap().period_number, "P2");
+        assert_eq!(day.next_lesson(at("11:00:00")).unwrap().period_number, "P3");
+        assert!(day.next_lesson(at("12:00:00")).is_none());
+        assert!(day.next_lesson(date("2023-09-27").and_hms_opt(8, 0, 0).unwrap()).is_none());
+
+        let free_periods = day.free_periods();
+        assert_eq!(free_periods.len(), 1);
+        assert_eq!(free_periods[0].number, "P4");
+
+        let gaps = day.gaps_between_lessons();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].start_time, at("10:45:00"));
+        assert_eq!(gaps[0].duration(), Duration::minutes(35));
+
+        let room_changes = day.room_changes();
+        assert_eq!(room_changes.len(), 1);
+        assert_eq!(room_changes[0].from.room_name, "M4");
+        assert_eq!(room_changes[0].to.room_name, "S2");
+    }
+
+    #[tokio::test]
+    async fn get_timetable_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        let timetable_dates = ["2023-09-29", "2023-10-02", "2023-10-03"];
+
+        // Create a mock on the server.
+        let responses: Vec<_> = [("2023-09-29", 1, "Maths"), ("2023-10-02", 2, "English"), ("2023-10-03", 2, "Science")]
+            .into_iter()
+            .map(|(date, week, subject)| {
+                return server.mock(|when, then| {
+                    when.method(GET)
+                        .path("/apiv2student/timetable/student_id")
+                        .query_param("date", date);
+                    then.status(200)
+                        .header("content-type", "application/json")
+                        .json_body(lessons_json(date, week, subject, &timetable_dates));
+                });
+            })
+            .collect();
+
+        let client = Client::generate_mock(server.base_url());
+
+        let timetable = client
+            .get_timetable(date("2023-09-29"), date("2023-10-03"))
+            .await
+            .unwrap();
+
+        // the weekend isn't fetched
+        assert_eq!(
+            timetable.days.keys().copied().collect::<Vec<_>>(),
+            vec![date("2023-09-29"), date("2023-10-02"), date("2023-10-03")]
+        );
+        assert_eq!(timetable.cycle().unwrap().length, 2);
+        assert_eq!(timetable.lessons().count(), 3);
+
+        for response in responses {
+            response.assert();
+        }
+    }
+}
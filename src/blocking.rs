@@ -0,0 +1,366 @@
+//! A blocking ClassCharts client, for programs which don't use an async runtime.
+//!
+//! This is only available with the `blocking` feature. Every method has the same name and returns
+//! the same types as its async counterpart on `classcharts::Client`.
+//!
+//! ```rust,no_run
+//! use classcharts::blocking::Client;
+//!
+//! let client = Client::create("your access code", "your date of birth (DD/MM/YYYY)", None).unwrap();
+//!
+//! let homework = client.get_homeworks(None).unwrap();
+//! ```
+//!
+//! The blocking client runs its own single threaded tokio runtime, so it **must not** be created,
+//! used or dropped from within an async context, use `classcharts::Client` there instead.
+
+use std::{
+    borrow::Cow,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chrono::NaiveDate;
+use tokio::runtime::Runtime;
+
+use crate::{
+    api::{
+        activity::{Activity, ActivityData, ActivityOptions, FullActivityOptions},
+        announcements::Announcements,
+        attachments::{AttachmentDownload, DownloadableAttachment, HasAttachments},
+        attendance::{Attendance, AttendanceOptions},
+        badges::Badges,
+        behaviour::{Behaviour, BehaviourOptions},
+        detentions::Detentions,
+        homework::{
+            HomeworkAttachmentDelete, HomeworkAttachmentUpload, HomeworkDetail, HomeworkMeta, HomeworkOptions,
+            HomeworkQuery, HomeworkStatus, HomeworkTicked, Homeworks,
+        },
+        lessons::{Lesson, Lessons},
+        pupilfields::PupilFields,
+        rewards::{RewardPurchase, Rewards},
+        student::{Student, StudentInfo},
+        timetable::Timetable,
+    },
+    builder::ClientBuilder,
+    client::{ClientCreationError, ErrorResponse, SessionState},
+};
+
+/// A blocking ClassCharts Student Client, which wraps a `classcharts::Client`.
+///
+/// Cloning the client is cheap, and every clone shares the same session and runtime.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Creates a blocking ClassCharts Student Client. See `classcharts::Client::create`.
+    ///
+    /// The `dob` parameter should be in the format of `DD/MM/YYYY`.
+    pub fn create<C, D>(
+        code: C,
+        dob: D,
+        base_url: Option<String>,
+    ) -> Result<Client, ClientCreationError>
+    where
+        C: ToString,
+        D: Into<Cow<'static, str>>,
+    {
+        let runtime = Client::build_runtime()?;
+        let inner = runtime.block_on(crate::Client::create(code, dob, base_url))?;
+
+        return Ok(Client::with_runtime(inner, runtime));
+    }
+
+    /// Logs in with a configured `ClientBuilder`, for timeouts, retries, etc.
+    pub fn login<C, D>(builder: ClientBuilder, code: C, dob: D) -> Result<Client, ClientCreationError>
+    where
+        C: ToString,
+        D: Into<Cow<'static, str>>,
+    {
+        let runtime = Client::build_runtime()?;
+        let inner = runtime.block_on(builder.login(code, dob))?;
+
+        return Ok(Client::with_runtime(inner, runtime));
+    }
+
+    /// Restores a session created by `export_session`. See `classcharts::Client::from_session`.
+    pub fn from_session(state: SessionState) -> Result<Client, ClientCreationError> {
+        let runtime = Client::build_runtime()?;
+        let inner = runtime.block_on(crate::Client::from_session(state))?;
+
+        return Ok(Client::with_runtime(inner, runtime));
+    }
+
+    /// Wraps an existing async client.
+    pub fn from_async(inner: crate::Client) -> Result<Client, ClientCreationError> {
+        return Ok(Client::with_runtime(inner, Client::build_runtime()?));
+    }
+
+    fn build_runtime() -> Result<Runtime, ClientCreationError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(ClientCreationError::RuntimeError)?;
+
+        return Ok(runtime);
+    }
+
+    fn with_runtime(inner: crate::Client, runtime: Runtime) -> Client {
+        return Client {
+            inner,
+            runtime: Arc::new(runtime),
+        };
+    }
+
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        return self.runtime.block_on(future);
+    }
+
+    /// The async client this wraps.
+    pub fn as_async(&self) -> &crate::Client {
+        return &self.inner;
+    }
+
+    /// The id of the student the client is logged in as.
+    pub fn student_id(&self) -> &str {
+        return &self.inner.student_id;
+    }
+
+    /// The current ClassCharts `session_id`.
+    pub fn session_id(&self) -> String {
+        return self.inner.session_id();
+    }
+
+    /// Exports the current session so it can be saved and later restored with `from_session`.
+    pub fn export_session(&self) -> SessionState {
+        return self.inner.export_session();
+    }
+
+    /// Get's a new `session_id` from ClassCharts. See `classcharts::Client::get_new_session_id`.
+    pub fn get_new_session_id(&self) -> Result<String, ErrorResponse> {
+        return self.block_on(self.inner.get_new_session_id());
+    }
+
+    /// Gets the current student's activity. See `classcharts::Client::get_activity`.
+    pub fn get_activity(&self, options: Option<ActivityOptions>) -> Result<Activity, ErrorResponse> {
+        return self.block_on(self.inner.get_activity(options));
+    }
+
+    /// Gets all of the current student's activity between two dates.
+    /// See `classcharts::Client::get_full_activity`.
+    pub fn get_full_activity(
+        &self,
+        options: FullActivityOptions,
+    ) -> Result<ActivityData, ErrorResponse> {
+        return self.block_on(self.inner.get_full_activity(options));
+    }
+
+    /// Gets the current student's announcements.
+    pub fn get_announcements(&self) -> Result<Announcements, ErrorResponse> {
+        return self.block_on(self.inner.get_announcements());
+    }
+
+    /// Downloads a homework or announcement attachment.
+    /// See `classcharts::Client::download_attachment`.
+    pub fn download_attachment<A>(&self, attachment: &A) -> Result<AttachmentDownload, ErrorResponse>
+    where
+        A: DownloadableAttachment + ?Sized,
+    {
+        return self.block_on(self.inner.download_attachment(attachment));
+    }
+
+    /// Downloads every attachment of a `Homework` or `Announcement` into `directory`.
+    /// See `classcharts::Client::save_attachments`.
+    pub fn save_attachments<T, P>(&self, item: &T, directory: P) -> Result<Vec<PathBuf>, ErrorResponse>
+    where
+        T: HasAttachments + ?Sized,
+        P: AsRef<Path>,
+    {
+        return self.block_on(self.inner.save_attachments(item, directory));
+    }
+
+    /// Gets the current student's attendance. See `classcharts::Client::get_attendance`.
+    pub fn get_attendance(
+        &self,
+        options: Option<AttendanceOptions>,
+    ) -> Result<Attendance, ErrorResponse> {
+        return self.block_on(self.inner.get_attendance(options));
+    }
+
+    /// Gets the current student's earned badges.
+    pub fn get_badges(&self) -> Result<Badges, ErrorResponse> {
+        return self.block_on(self.inner.get_badges());
+    }
+
+    /// Gets the current student's behaviour. See `classcharts::Client::get_behaviour`.
+    pub fn get_behaviour(
+        &self,
+        options: Option<BehaviourOptions>,
+    ) -> Result<Behaviour, ErrorResponse> {
+        return self.block_on(self.inner.get_behaviour(options));
+    }
+
+    /// Gets the current student's detentions.
+    pub fn get_detentions(&self) -> Result<Detentions, ErrorResponse> {
+        return self.block_on(self.inner.get_detentions());
+    }
+
+    /// Gets the current student's homework. See `classcharts::Client::get_homeworks`.
+    pub fn get_homeworks(&self, options: Option<HomeworkOptions>) -> Result<Homeworks, ErrorResponse> {
+        return self.block_on(self.inner.get_homeworks(options));
+    }
+
+    /// Gets a single piece of homework. See `classcharts::Client::get_homework`.
+    pub fn get_homework(&self, homework_id: usize) -> Result<HomeworkDetail, ErrorResponse> {
+        return self.block_on(self.inner.get_homework(homework_id));
+    }
+
+    /// Gets the current student's homework matching a `HomeworkQuery`.
+    /// See `classcharts::Client::query_homeworks`.
+    pub fn query_homeworks(&self, query: &HomeworkQuery) -> Result<Homeworks, ErrorResponse> {
+        return self.block_on(self.inner.query_homeworks(query));
+    }
+
+    /// Ticks (or unticks) a piece of homework. See `classcharts::Client::tick_homework`.
+    pub fn tick_homework(
+        &self,
+        homework_status_id: usize,
+        ticked: bool,
+    ) -> Result<HomeworkTicked, ErrorResponse> {
+        return self.block_on(self.inner.tick_homework(homework_status_id, ticked));
+    }
+
+    /// Attaches a file to a homework submission.
+    /// See `classcharts::Client::upload_homework_attachment`.
+    pub fn upload_homework_attachment(
+        &self,
+        status: &HomeworkStatus,
+        meta: &HomeworkMeta,
+        file_name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<HomeworkAttachmentUpload, ErrorResponse> {
+        return self.block_on(
+            self.inner
+                .upload_homework_attachment(status, meta, file_name, bytes),
+        );
+    }
+
+    /// Removes a file from a homework submission.
+    pub fn delete_homework_attachment(
+        &self,
+        attachment_id: usize,
+    ) -> Result<HomeworkAttachmentDelete, ErrorResponse> {
+        return self.block_on(self.inner.delete_homework_attachment(attachment_id));
+    }
+
+    /// Gets the current student's lessons for a given date.
+    pub fn get_lessons(&self, date: NaiveDate) -> Result<Lessons, ErrorResponse> {
+        return self.block_on(self.inner.get_lessons(date));
+    }
+
+    /// Gets the current student's lessons between two dates (inclusive).
+    /// See `classcharts::Client::get_timetable`.
+    pub fn get_timetable(&self, from: NaiveDate, to: NaiveDate) -> Result<Timetable, ErrorResponse> {
+        return self.block_on(self.inner.get_timetable(from, to));
+    }
+
+    /// Gets the current student's lessons between two dates as a `Calendar`.
+    /// See `classcharts::Client::get_timetable_calendar`.
+    #[cfg(feature = "ics")]
+    pub fn get_timetable_calendar(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<crate::ics::Calendar, ErrorResponse> {
+        return self.block_on(self.inner.get_timetable_calendar(from, to));
+    }
+
+    /// Sets the current student's own note on a lesson. See `classcharts::Client::set_lesson_note`.
+    pub fn set_lesson_note(
+        &self,
+        student: &Student,
+        lesson_key: usize,
+        date: NaiveDate,
+        text: &str,
+    ) -> Result<Lesson, ErrorResponse> {
+        return self.block_on(self.inner.set_lesson_note(student, lesson_key, date, text));
+    }
+
+    /// Removes the current student's own note from a lesson.
+    pub fn clear_lesson_note(
+        &self,
+        student: &Student,
+        lesson_key: usize,
+        date: NaiveDate,
+    ) -> Result<Lesson, ErrorResponse> {
+        return self.block_on(self.inner.clear_lesson_note(student, lesson_key, date));
+    }
+
+    /// Gets the current student's custom fields.
+    pub fn get_pupilfields(&self) -> Result<PupilFields, ErrorResponse> {
+        return self.block_on(self.inner.get_pupilfields());
+    }
+
+    /// Gets the available items in the current student's rewards shop.
+    pub fn get_rewards(&self) -> Result<Rewards, ErrorResponse> {
+        return self.block_on(self.inner.get_rewards());
+    }
+
+    /// Purchase a reward item from the current student's rewards shop.
+    /// See `classcharts::Client::purchase_reward`.
+    pub fn purchase_reward<T>(&self, item_id: T) -> Result<RewardPurchase, ErrorResponse>
+    where
+        T: std::fmt::Display,
+    {
+        return self.block_on(self.inner.purchase_reward(item_id));
+    }
+
+    /// Gets the current student's info.
+    pub fn get_student_info(&self) -> Result<StudentInfo, ErrorResponse> {
+        return self.block_on(self.inner.get_student_info());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn blocking_client_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET).path("/apiv2student/customfields/student_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "Note",
+                        "fields": []
+                    },
+                    "meta": []
+                }));
+        });
+
+        let client = Client::from_async(crate::Client::generate_mock(server.base_url())).unwrap();
+
+        let pupilfields = client.get_pupilfields().unwrap();
+
+        assert_eq!(pupilfields.data.note, "Note");
+        assert_eq!(client.student_id(), "student_id");
+
+        pupilfields_response.assert();
+    }
+}
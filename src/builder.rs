@@ -0,0 +1,381 @@
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::{
+    client::{
+        Client, ClientCreationError, LoginCredentials, SessionState,
+        DEFAULT_BASE_URL, PARENT_API_PATH, STUDENT_API_PATH,
+    },
+    parent::ParentClient,
+    retry::{RateLimit, RetryPolicy},
+    transport::{ReqwestTransport, Transport},
+};
+
+/// Configures and logs in a `Client` or `ParentClient`.
+///
+/// Example:
+/// ```rust,no_run
+/// use classcharts::ClientBuilder;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = ClientBuilder::new()
+///     .timeout(Duration::from_secs(10))
+///     .user_agent("my-app/1.0")
+///     .login("your access code", "your date of birth (DD/MM/YYYY)")
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    base_url: Option<String>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<reqwest::Proxy>,
+    default_headers: HeaderMap,
+    session_refresh_interval: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl ClientBuilder {
+    pub fn new() -> ClientBuilder {
+        return ClientBuilder::default();
+    }
+
+    /// Sets the ClassCharts url, this should **rarely** be used and is only implimented for
+    /// testing. Defaults to `https://www.classcharts.com`.
+    pub fn base_url<U>(mut self, base_url: U) -> ClientBuilder
+    where
+        U: Into<String>,
+    {
+        self.base_url = Some(base_url.into());
+        return self;
+    }
+
+    /// Sets the timeout for connecting to ClassCharts.
+    pub fn connect_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.connect_timeout = Some(timeout);
+        return self;
+    }
+
+    /// Sets the timeout for each request, from connecting until the response body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.timeout = Some(timeout);
+        return self;
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    pub fn user_agent<U>(mut self, user_agent: U) -> ClientBuilder
+    where
+        U: Into<String>,
+    {
+        self.user_agent = Some(user_agent.into());
+        return self;
+    }
+
+    /// Sends every request through an HTTP(S) proxy.
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> ClientBuilder {
+        self.proxy = Some(proxy);
+        return self;
+    }
+
+    /// Adds a header which is sent with every request.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> ClientBuilder {
+        self.default_headers.insert(name, value);
+        return self;
+    }
+
+    /// Adds headers which are sent with every request.
+    pub fn default_headers(mut self, headers: HeaderMap) -> ClientBuilder {
+        self.default_headers.extend(headers);
+        return self;
+    }
+
+    /// Sets how long a `session_id` is used for before a new one is requested. Defaults to
+    /// `DEFAULT_SESSION_REFRESH_INTERVAL` (3 minutes).
+    pub fn session_refresh_interval(mut self, interval: Duration) -> ClientBuilder {
+        self.session_refresh_interval = Some(interval);
+        return self;
+    }
+
+    /// Sets when failed requests are retried. Defaults to `RetryPolicy::default()`, which retries
+    /// `GET` requests up to 3 times. Use `RetryPolicy::none()` to disable retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> ClientBuilder {
+        self.retry_policy = Some(retry_policy);
+        return self;
+    }
+
+    /// Limits how many requests are sent to ClassCharts. There is no limit by default.
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> ClientBuilder {
+        self.rate_limit = Some(rate_limit);
+        return self;
+    }
+
+    /// Uses an existing `reqwest::Client` instead of building one. The timeouts, user agent, proxy
+    /// and default headers set on this builder are then ignored.
+    ///
+    /// The client **must not** follow redirects, as ClassCharts responds to a successful login
+    /// with a redirect which sets the session cookies.
+    pub fn reqwest_client(self, reqwest_client: reqwest::Client) -> ClientBuilder {
+        return self.transport(Arc::new(ReqwestTransport::new(reqwest_client)));
+    }
+
+    /// Sends requests through a custom `Transport` instead of `reqwest`. The timeouts, user agent,
+    /// proxy and default headers set on this builder are then ignored.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> ClientBuilder {
+        self.transport = Some(transport);
+        return self;
+    }
+
+    /// Logs in as a student. The `dob` parameter should be in the format of `DD/MM/YYYY`.
+    /// See `Client::create`.
+    pub async fn login<C, D>(self, code: C, dob: D) -> Result<Client, ClientCreationError>
+    where
+        C: ToString,
+        D: Into<Cow<'static, str>>,
+    {
+        let credentials = LoginCredentials::Student {
+            code: code.to_string(),
+            dob: dob.into().into_owned(),
+        };
+
+        let mut client = self.login_with(credentials, STUDENT_API_PATH).await?;
+
+        let cc_response = client
+            .get_student_info()
+            .await
+            .map_err(ClientCreationError::ApiRequestError)?;
+
+        client.student_id = cc_response.data.user.id.to_string();
+
+        return Ok(client);
+    }
+
+    /// Logs in as a parent. See `ParentClient::create`.
+    pub async fn login_parent<E, P>(
+        self,
+        email: E,
+        password: P,
+    ) -> Result<ParentClient, ClientCreationError>
+    where
+        E: ToString,
+        P: ToString,
+    {
+        let credentials = LoginCredentials::Parent {
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+
+        let client = self.login_with(credentials, PARENT_API_PATH).await?;
+        let mut parent_client = ParentClient::from_client(client, vec![]);
+
+        let pupils = parent_client
+            .get_pupils()
+            .await
+            .map_err(ClientCreationError::ApiRequestError)?;
+
+        let first_pupil = pupils.data.first().ok_or(ClientCreationError::NoPupilsError)?.id;
+        parent_client.pupils = pupils.data;
+        parent_client.select_pupil(first_pupil)?;
+
+        return Ok(parent_client);
+    }
+
+    /// Restores a session created by `Client::export_session`. See `Client::from_session`.
+    /// The `base_url` set on this builder is ignored in favour of the one in the session.
+    pub async fn restore_session(self, state: SessionState) -> Result<Client, ClientCreationError> {
+        let session_refresh_interval = self.session_refresh_interval;
+        let retry_policy = self.retry_policy.clone().unwrap_or_default();
+        let rate_limit = self.rate_limit;
+        let transport = self.build_transport()?;
+
+        let client = Client::from_parts(
+            transport,
+            state.student_id,
+            state.base_url,
+            state.api_path,
+            state.auth_cookies,
+            state.session_id,
+            retry_policy,
+            rate_limit,
+        );
+        client.set_last_session_id_updated(state.last_session_id_updated);
+
+        if let Some(interval) = session_refresh_interval {
+            client.set_session_refresh_interval(interval);
+        }
+
+        client
+            .get_new_session_id()
+            .await
+            .map_err(ClientCreationError::from_restore_error)?;
+
+        return Ok(client);
+    }
+
+    async fn login_with(
+        self,
+        credentials: LoginCredentials,
+        api_path: &str,
+    ) -> Result<Client, ClientCreationError> {
+        let session_refresh_interval = self.session_refresh_interval;
+        let retry_policy = self.retry_policy.clone().unwrap_or_default();
+        let rate_limit = self.rate_limit;
+        let base_url = self
+            .base_url
+            .clone()
+            .unwrap_or(DEFAULT_BASE_URL.to_string());
+        let transport = self.build_transport()?;
+
+        let (session_id, auth_cookies) = Client::login(&transport, &base_url, &credentials).await?;
+
+        let client = Client::from_parts(
+            transport,
+            "".to_string(),
+            base_url,
+            api_path.to_string(),
+            auth_cookies,
+            session_id,
+            retry_policy,
+            rate_limit,
+        );
+        client.set_credentials(credentials);
+
+        if let Some(interval) = session_refresh_interval {
+            client.set_session_refresh_interval(interval);
+        }
+
+        return Ok(client);
+    }
+
+    fn build_transport(self) -> Result<Arc<dyn Transport>, ClientCreationError> {
+        if let Some(transport) = self.transport {
+            return Ok(transport);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .default_headers(self.default_headers);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+
+        return Ok(Arc::new(ReqwestTransport::new(builder.build()?)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn builder_login_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let student_login_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/student/login")
+                .header("user-agent", "classcharts-test")
+                .header("x-test", "yes");
+            then.status(302).header(
+                "set-cookie",
+                "student_session_credentials={\"session_id\":\"jf99rm23pdi29dj32fh23i\"}",
+            );
+        });
+
+        let student_info_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/ping")
+                .header("user-agent", "classcharts-test")
+                .header("x-test", "yes");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "user": {
+                            "id": 3949234,
+                            "name": "Name",
+                            "first_name": "first_name",
+                            "last_name": "last_name",
+                            "avatar_url": "https://example.com",
+                            "display_behaviour": false,
+                            "display_parent_behaviour": false,
+                            "display_homework": false,
+                            "display_rewards": false,
+                            "display_detentions": false,
+                            "display_report_cards": false,
+                            "display_classes": false,
+                            "display_announcements": true,
+                            "display_academic_reports": false,
+                            "display_attendance": true,
+                            "display_attendance_type": "instance",
+                            "display_attendance_percentage": false,
+                            "display_activity": false,
+                            "display_mental_health": false,
+                            "display_mental_health_no_tracker": false,
+                            "display_timetable": false,
+                            "is_disabled": false,
+                            "display_two_way_communications": true,
+                            "display_absences": false,
+                            "can_upload_attachments": false,
+                            "display_event_badges": false,
+                            "display_avatars": false,
+                            "display_concern_submission": false,
+                            "display_custom_fields": false,
+                            "pupil_concerns_help_text": "",
+                            "allow_pupils_add_timetable_notes": false,
+                            "detention_alias_plural_uc": "Detentions",
+                            "announcements_count": 0,
+                            "messages_count": 0,
+                            "pusher_channel_name": "pusher_channel_name",
+                            "has_birthday": false,
+                            "has_new_survey": false,
+                            "survey_id": null
+                        }
+                    },
+                    "meta": {
+                        "session_id": "jf99rm23pdi29dj32fh23i",
+                        "version": "27.16.2",
+                    },
+                }));
+        });
+
+        let client = ClientBuilder::new()
+            .base_url(server.base_url())
+            .timeout(Duration::from_secs(10))
+            .connect_timeout(Duration::from_secs(5))
+            .user_agent("classcharts-test")
+            .default_header(
+                HeaderName::from_static("x-test"),
+                HeaderValue::from_static("yes"),
+            )
+            .login("my_code", "my_dob")
+            .await
+            .unwrap();
+
+        assert_eq!(client.student_id, "3949234");
+
+        student_login_response.assert();
+        student_info_response.assert();
+    }
+}
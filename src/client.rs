@@ -1,21 +1,144 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use std::{borrow::Cow, string::FromUtf8Error};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fmt,
+    string::FromUtf8Error,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use thiserror::Error;
+use url::Url;
 
-use reqwest::{header::ToStrError, IntoUrl, RequestBuilder, Response};
+use reqwest::{header::ToStrError, Method, Response, StatusCode};
 
-use crate::{api::student::StudentInfoData, new_params};
+use crate::{
+    api::student::StudentInfoData,
+    builder::ClientBuilder,
+    multipart::Multipart,
+    new_params,
+    retry::{self, RateLimit, RateLimiter, RetryPolicy},
+    transport::{ReqwestTransport, RequestBuilder, Transport, TransportResponse},
+};
 
-#[derive(Debug)]
+pub(crate) const DEFAULT_BASE_URL: &str = "https://www.classcharts.com";
+pub(crate) const STUDENT_API_PATH: &str = "/apiv2student";
+pub(crate) const PARENT_API_PATH: &str = "/apiv2parent";
+
+/// How long a `session_id` is used for before a new one is requested from ClassCharts.
+pub const DEFAULT_SESSION_REFRESH_INTERVAL: Duration = Duration::from_secs(3 * 60);
+
+/// A ClassCharts Student Client.
+///
+/// Cloning a client is cheap, and every clone shares the same session, so a single client can be
+/// used from many tasks at once (for example in an axum `State`). The `student_id` is not shared,
+/// so changing it only affects that clone.
+#[derive(Debug, Clone)]
 pub struct Client {
-    pub session_id: String,
     pub student_id: String,
-    reqwest_client: reqwest::Client,
+    shared: Arc<SharedState>,
+}
+
+#[derive(Debug)]
+struct SharedState {
+    transport: Arc<dyn Transport>,
     base_url: String,
+    api_path: String,
+    auth: RwLock<AuthState>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    // held while the session is being refreshed, so concurrent callers wait for one `/ping`
+    // rather than all sending their own
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug, Clone)]
+struct AuthState {
+    session_id: String,
     auth_cookies: String,
     last_session_id_updated: DateTime<Utc>,
+    session_refresh_interval: Duration,
+    credentials: Option<LoginCredentials>,
+}
+
+/// The credentials a client logged in with, kept so it can log in again once the session can no
+/// longer be refreshed.
+#[derive(Clone)]
+pub(crate) enum LoginCredentials {
+    Student { code: String, dob: String },
+    Parent { email: String, password: String },
+}
+
+impl fmt::Debug for LoginCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoginCredentials::Student { .. } => f.write_str("Student { .. }"),
+            LoginCredentials::Parent { .. } => f.write_str("Parent { .. }"),
+        }
+    }
+}
+
+impl LoginCredentials {
+    fn login_path(&self) -> &'static str {
+        match self {
+            LoginCredentials::Student { .. } => "/student/login",
+            LoginCredentials::Parent { .. } => "/parent/login",
+        }
+    }
+
+    fn cookie_name(&self) -> &'static str {
+        match self {
+            LoginCredentials::Student { .. } => "student_session_credentials",
+            LoginCredentials::Parent { .. } => "parent_session_credentials",
+        }
+    }
+
+    fn login_form(&self) -> Multipart {
+        match self {
+            LoginCredentials::Student { code, dob } => Multipart::new()
+                .text("_method", "POST")
+                .text("code", &code.to_uppercase())
+                .text("dob", dob)
+                .text("remember_me", "1")
+                .text("recaptcha-token", "no-token-available"),
+            LoginCredentials::Parent { email, password } => Multipart::new()
+                .text("_method", "POST")
+                .text("email", email)
+                .text("logintype", "existing")
+                .text("password", password)
+                .text("recaptcha-token", "no-token-available"),
+        }
+    }
+}
+
+/// The body of a request sent by `Client::request`, with its content type.
+struct RequestBody {
+    content_type: String,
+    data: Vec<u8>,
+}
+
+impl RequestBody {
+    fn form(params: String) -> RequestBody {
+        return RequestBody {
+            content_type: "application/x-www-form-urlencoded".to_string(),
+            data: params.into_bytes(),
+        };
+    }
+}
+
+/// Everything needed to restore a `Client` without logging in again.
+/// Created with `Client::export_session` and restored with `Client::from_session`.
+///
+/// This contains the authentication cookies, so it should be stored as securely as a password.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionState {
+    pub base_url: String,
+    pub api_path: String,
+    pub student_id: String,
+    pub session_id: String,
+    pub auth_cookies: String,
+    pub last_session_id_updated: DateTime<Utc>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -29,7 +152,7 @@ pub struct CCStatusResponse {
     pub success: usize,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SuccessResponse<Data, Meta> {
     pub data: Data,
     pub meta: Meta,
@@ -59,6 +182,38 @@ pub enum ClientCreationError {
 
     #[error("Failed to decode the cookie")]
     StringDecodingError(#[from] FromUtf8Error),
+
+    #[error("The parent account does not have any pupils linked to it")]
+    NoPupilsError,
+
+    #[error("The saved session is not for a parent account")]
+    NotParentSessionError,
+
+    #[error("The saved pupil {0} is not linked to this parent account")]
+    UnknownPupilError(String),
+
+    #[error("The saved session has expired, you will need to log in again")]
+    SessionExpiredError(#[source] ErrorResponse),
+
+    #[error("Failed to start the runtime for the blocking client")]
+    RuntimeError(#[source] std::io::Error),
+}
+
+impl ClientCreationError {
+    /// Maps an error from the `/ping` which validates a restored session. ClassCharts either
+    /// rejects the ping or redirects to the login page (which isn't json) once the remember me
+    /// cookie has expired.
+    pub(crate) fn from_restore_error(err: ErrorResponse) -> ClientCreationError {
+        return match err {
+            ErrorResponse::SessionExpiredError
+            | ErrorResponse::ClassChartsError(..)
+            | ErrorResponse::ClassChartsStatusError(_)
+            | ErrorResponse::SerdeJsonParsingError(_) => {
+                ClientCreationError::SessionExpiredError(err)
+            }
+            err => ClientCreationError::ApiRequestError(err),
+        };
+    }
 }
 
 #[async_trait]
@@ -82,15 +237,70 @@ pub enum ErrorResponse {
 
     #[error("ClassCharts returned the error code: {0} and message {1}")]
     ClassChartsError(usize, String),
+
+    #[error("The pupil {0} is not linked to this parent account")]
+    UnknownPupilError(usize),
+
+    #[error("ClassCharts rejected the session")]
+    SessionExpiredError,
+
+    #[error("Failed to log in again after the session expired")]
+    ReauthenticationError(#[source] Box<ClientCreationError>),
+
+    #[error("The request could not be built: {0}")]
+    InvalidRequestError(String),
+
+    #[error("The response body is not valid UTF-8")]
+    BodyDecodingError(#[from] FromUtf8Error),
+
+    #[error("The attachment can not be uploaded: {0}")]
+    InvalidAttachmentError(String),
+
+    #[error("Failed to read or write a file")]
+    FileError(#[from] std::io::Error),
+
+    #[error("ClassCharts responded with the HTTP status {0}")]
+    HttpStatusError(StatusCode),
+
+    #[error("The school doesn't allow pupils to add notes to lessons")]
+    LessonNotesDisabledError,
+
+    #[error("There is no lesson {0} on that date")]
+    UnknownLessonError(usize),
+
+    #[error("The transport failed to send the request")]
+    TransportError(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
 
-#[derive(Deserialize, Debug)]
+impl ErrorResponse {
+    /// Whether this error means the session is no longer valid, either because ClassCharts
+    /// responded with `401 Unauthorized` (or redirected to the login page) or because it returned
+    /// its "Session expired" error message.
+    pub fn is_session_expired(&self) -> bool {
+        match self {
+            ErrorResponse::SessionExpiredError => true,
+            ErrorResponse::ClassChartsError(_, message) => {
+                message.trim().eq_ignore_ascii_case(SESSION_EXPIRED_MESSAGE)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The error message ClassCharts returns once a `session_id` is no longer accepted.
+const SESSION_EXPIRED_MESSAGE: &str = "Session expired";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SessionMeta {
     pub session_id: String,
 }
 
 pub type Session = SuccessResponse<StudentInfoData, SessionMeta>;
 
+/// The `/ping` response as it is read when refreshing the session. The `data` differs between
+/// the student and parent APIs, and only the `session_id` in the meta is needed, so it is ignored.
+type PingResponse = SuccessResponse<IgnoredAny, SessionMeta>;
+
 #[async_trait]
 impl CCParser for Response {
     /// Parses a reqwest::Response against ClassCharts API "spec".
@@ -113,24 +323,29 @@ impl CCParser for Response {
             .await
             .map_err(ErrorResponse::TextParsingError)?;
 
-        let json = serde_json::from_str::<CCStatusResponse>(&text)?;
+        return cc_parse_text(text);
+    }
+}
 
-        if json.success != 1 {
-            if let Some(error) = json.error {
-                return Err(ErrorResponse::ClassChartsError(json.success, error));
-            } else {
-                return Err(ErrorResponse::ClassChartsStatusError(json.success));
-            }
-        }
+/// Checks a response body is a `{ success: 1 }`, which is shared by every `CCParser`.
+pub(crate) fn cc_parse_text(text: String) -> Result<String, ErrorResponse> {
+    let json = serde_json::from_str::<CCStatusResponse>(&text)?;
 
-        return Ok(text);
+    if json.success != 1 {
+        if let Some(error) = json.error {
+            return Err(ErrorResponse::ClassChartsError(json.success, error));
+        } else {
+            return Err(ErrorResponse::ClassChartsStatusError(json.success));
+        }
     }
+
+    return Ok(text);
 }
 
 impl Client {
     /// Builds a get reqwest, injecting ClassCharts Authorization cookies and headers. 
-    /// The url is formed by appending `/apiv2student` to the `base_url` set on the client and then
-    /// appending the path you set onto that.
+    /// The url is formed by appending the API path (`/apiv2student`, or `/apiv2parent` for parent
+    /// clients) to the `base_url` set on the client and then appending the path you set onto that.
     /// 
     /// Example:
     /// ```ignore
@@ -142,24 +357,16 @@ impl Client {
     /// ```
     ///
     /// For `POST` requests use `.build_post`.
-    pub async fn build_get<P>(&mut self, path: P) -> Result<RequestBuilder, ErrorResponse>
+    pub async fn build_get<P>(&self, path: P) -> Result<RequestBuilder, ErrorResponse>
     where
-        P: IntoUrl + std::fmt::Display,
+        P: std::fmt::Display,
     {
-        if (self.last_session_id_updated.time() - Utc::now().time()).num_minutes() > 3 {
-            self.get_new_session_id().await?;
-        }
-
-        return Ok(self
-            .reqwest_client
-            .get(format!("{}/apiv2student{}", self.base_url, path))
-            .header("Cookie", &self.auth_cookies)
-            .header("Authorization", format!("Basic {}", self.session_id)));
+        return self.build_request(Method::GET, path).await;
     }
 
     /// Builds a post reqwest, injecting ClassCharts Authorization cookies and headers. 
-    /// The url is formed by appending `/apiv2student` to the `base_url` set on the client and then
-    /// appending the path you set onto that.
+    /// The url is formed by appending the API path (`/apiv2student`, or `/apiv2parent` for parent
+    /// clients) to the `base_url` set on the client and then appending the path you set onto that.
     /// 
     /// Example:
     /// ```ignore
@@ -171,47 +378,326 @@ impl Client {
     /// ```
     ///
     /// For `GET` requests use `.build_post`.
-    pub async fn build_post<P>(&mut self, path: P) -> Result<RequestBuilder, ErrorResponse>
+    pub async fn build_post<P>(&self, path: P) -> Result<RequestBuilder, ErrorResponse>
     where
-        P: IntoUrl + std::fmt::Display,
+        P: std::fmt::Display,
     {
-        if (self.last_session_id_updated.time() - Utc::now().time()).num_minutes() > 3 {
-            self.get_new_session_id().await?;
+        return self.build_request(Method::POST, path).await;
+    }
+
+    async fn build_request<P>(&self, method: Method, path: P) -> Result<RequestBuilder, ErrorResponse>
+    where
+        P: std::fmt::Display,
+    {
+        let url = format!("{}{}{}", self.shared.base_url, self.shared.api_path, path);
+
+        return self.build_authenticated_request(method, &url).await;
+    }
+
+    /// Resolves the url of a file hosted by ClassCharts, such as a homework attachment, against
+    /// the `base_url`.
+    pub(crate) fn resolve_file_url(&self, url: &str) -> Result<Url, ErrorResponse> {
+        return Url::parse(&self.shared.base_url)
+            .and_then(|base_url| base_url.join(url))
+            .map_err(|err| ErrorResponse::InvalidRequestError(err.to_string()));
+    }
+
+    /// Builds a `GET` request for a file. The session is only sent if `send_session` is set and
+    /// the file is hosted on the same origin as the `base_url`.
+    pub(crate) async fn build_file_get(
+        &self,
+        url: &Url,
+        send_session: bool,
+    ) -> Result<RequestBuilder, ErrorResponse> {
+        let base_url = self.resolve_file_url("/")?;
+
+        if send_session && url.origin() == base_url.origin() {
+            return self.build_authenticated_request(Method::GET, url.as_str()).await;
+        }
+
+        return Ok(
+            RequestBuilder::new(self.shared.transport.clone(), Method::GET, url.as_str())
+                .rate_limiter(self.shared.rate_limiter.clone()),
+        );
+    }
+
+    async fn build_authenticated_request(
+        &self,
+        method: Method,
+        url: &str,
+    ) -> Result<RequestBuilder, ErrorResponse> {
+        if self.session_needs_refresh() {
+            let _guard = self.shared.refresh_lock.lock().await;
+
+            // another caller may have refreshed the session while we were waiting
+            if self.session_needs_refresh() {
+                self.refresh_session().await?;
+            }
+        }
+
+        let auth = self.auth();
+
+        return Ok(RequestBuilder::new(
+            self.shared.transport.clone(),
+            method,
+            url,
+        )
+        .rate_limiter(self.shared.rate_limiter.clone())
+        .header("Cookie", auth.auth_cookies)
+        .header("Authorization", format!("Basic {}", auth.session_id)));
+    }
+
+    /// Sends a request to the ClassCharts API and parses it with `cc_parse`.
+    /// `path` is appended to the API path in the same way as `build_get` / `build_post`, and
+    /// `body` is sent as a url encoded form.
+    ///
+    /// Failed requests are retried according to the client's `RetryPolicy`. If ClassCharts
+    /// reports that the session has expired, the session is refreshed (or the client logs in
+    /// again, if it was created with credentials) and the request is sent once more.
+    pub(crate) async fn request(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<String>,
+    ) -> Result<String, ErrorResponse> {
+        return self
+            .request_with_retry(method, path, body.map(RequestBody::form), true)
+            .await;
+    }
+
+    /// The same as `request`, but never retried by the `RetryPolicy`, for requests which must not
+    /// be sent twice.
+    pub(crate) async fn request_once(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<String>,
+    ) -> Result<String, ErrorResponse> {
+        return self
+            .request_with_retry(method, path, body.map(RequestBody::form), false)
+            .await;
+    }
+
+    /// Sends a `multipart/form-data` `POST` request, which is never retried by the `RetryPolicy`.
+    pub(crate) async fn request_multipart(
+        &self,
+        path: String,
+        form: Multipart,
+    ) -> Result<String, ErrorResponse> {
+        let body = RequestBody {
+            content_type: form.content_type(),
+            data: form.finish(),
+        };
+
+        return self
+            .request_with_retry(Method::POST, path, Some(body), false)
+            .await;
+    }
+
+    async fn request_with_retry(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<RequestBody>,
+        retry: bool,
+    ) -> Result<String, ErrorResponse> {
+        let retry = retry && self.shared.retry_policy.allows_method(&method);
+        let session_id = self.session_id();
+
+        match self.send_with_retries(&method, &path, &body, retry).await {
+            Err(err) if err.is_session_expired() => {
+                self.reauthenticate(&session_id).await?;
+
+                return self.send_with_retries(&method, &path, &body, retry).await;
+            }
+            result => return result,
         }
+    }
+
+    async fn send_with_retries(
+        &self,
+        method: &Method,
+        path: &str,
+        body: &Option<RequestBody>,
+        retry: bool,
+    ) -> Result<String, ErrorResponse> {
+        let policy = &self.shared.retry_policy;
+        let mut attempt = 1;
+
+        loop {
+            let result = self.send_request(method, path, body).await;
+
+            let backoff = match &result {
+                Ok(response) if policy.retries_status(response.status) => {
+                    Some(policy.backoff(attempt, retry::retry_after(&response.headers)))
+                }
+                Err(err) if policy.retries_error(err) => Some(policy.backoff(attempt, None)),
+                _ => None,
+            };
+
+            if let Some(backoff) = backoff {
+                if retry && attempt < policy.max_attempts {
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
 
-        return Ok(self
-            .reqwest_client
-            .post(format!("{}/apiv2student{}", self.base_url, path))
-            .header("Cookie", &self.auth_cookies)
-            .header("Authorization", format!("Basic {}", self.session_id)));
+            let response = result?;
+
+            if policy.retries_status(response.status) {
+                return Err(ErrorResponse::HttpStatusError(response.status));
+            }
+
+            return Client::parse_response(response).await;
+        }
+    }
+
+    async fn send_request(
+        &self,
+        method: &Method,
+        path: &str,
+        body: &Option<RequestBody>,
+    ) -> Result<TransportResponse, ErrorResponse> {
+        let mut request = self.build_request(method.clone(), path).await?;
+
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, body.content_type.as_str())
+                .body(body.data.clone());
+        }
+
+        return request.send().await;
+    }
+
+    async fn parse_response(response: TransportResponse) -> Result<String, ErrorResponse> {
+        let status = response.status;
+
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FOUND {
+            return Err(ErrorResponse::SessionExpiredError);
+        }
+
+        return response.cc_parse().await;
+    }
+
+    /// Gets a new session after ClassCharts has rejected `stale_session_id`. If the `/ping` fails
+    /// and the client holds credentials, it logs in again.
+    async fn reauthenticate(&self, stale_session_id: &str) -> Result<(), ErrorResponse> {
+        let _guard = self.shared.refresh_lock.lock().await;
+
+        // another caller has already replaced the rejected session
+        if self.session_id() != stale_session_id {
+            return Ok(());
+        }
+
+        return self.refresh_session().await;
+    }
+
+    /// Refreshes the session with a `/ping`. If the ping fails and the client holds credentials,
+    /// it logs in again. Callers must hold the `refresh_lock`.
+    async fn refresh_session(&self) -> Result<(), ErrorResponse> {
+        let err = match self.ping().await {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        let Some(credentials) = self.auth().credentials else {
+            return Err(err);
+        };
+
+        let (session_id, auth_cookies) =
+            Client::login(&self.shared.transport, &self.shared.base_url, &credentials)
+                .await
+                .map_err(|err| ErrorResponse::ReauthenticationError(Box::new(err)))?;
+
+        let mut auth = self.auth_mut();
+        auth.session_id = session_id;
+        auth.auth_cookies = auth_cookies;
+        auth.last_session_id_updated = Utc::now();
+
+        return Ok(());
+    }
+
+    /// Whether `session_refresh_interval` has elapsed since the `session_id` was last updated.
+    fn session_needs_refresh(&self) -> bool {
+        let auth = self.auth();
+        let elapsed = Utc::now().signed_duration_since(auth.last_session_id_updated);
+
+        // a negative duration means the clock has gone backwards, so the age of the session is
+        // unknown
+        return match elapsed.to_std() {
+            Ok(elapsed) => elapsed >= auth.session_refresh_interval,
+            Err(_) => true,
+        };
+    }
+
+    fn auth(&self) -> AuthState {
+        return self
+            .shared
+            .auth
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone();
+    }
+
+    fn auth_mut(&self) -> std::sync::RwLockWriteGuard<'_, AuthState> {
+        return self
+            .shared
+            .auth
+            .write()
+            .unwrap_or_else(|err| err.into_inner());
+    }
+
+    /// The current ClassCharts `session_id`.
+    pub fn session_id(&self) -> String {
+        return self.auth().session_id;
+    }
+
+    /// Sets how long a `session_id` is used for before a new one is requested. Defaults to
+    /// `DEFAULT_SESSION_REFRESH_INTERVAL` (3 minutes). This applies to every clone of the client.
+    pub fn set_session_refresh_interval(&self, interval: Duration) {
+        self.auth_mut().session_refresh_interval = interval;
     }
 
     /// Get's a new `session_id` from ClassCharts. It does two things:
     /// - Returns this id 
-    /// - Sets the `session_id` and `last_session_id_updated` properties on self.
-    pub async fn get_new_session_id(&mut self) -> Result<String, ErrorResponse> {
+    /// - Sets the `session_id` and `last_session_id_updated`, which are shared by every clone of
+    ///   the client.
+    pub async fn get_new_session_id(&self) -> Result<String, ErrorResponse> {
+        let _guard = self.shared.refresh_lock.lock().await;
+
+        return self.ping().await;
+    }
+
+    /// Sends the `/ping` which refreshes the session. Callers must hold the `refresh_lock`.
+    async fn ping(&self) -> Result<String, ErrorResponse> {
         let params = new_params!("include_data", "true");
+        let auth = self.auth();
 
-        let request = self
-            .reqwest_client
-            .post(format!("{}/apiv2student/ping", self.base_url))
-            .header("Cookie", &self.auth_cookies)
-            .header("Authorization", format!("Basic {}", self.session_id))
-            .header(
-                reqwest::header::CONTENT_TYPE,
-                "application/x-www-form-urlencoded",
-            )
-            .body(params)
-            .send()
-            .await?;
+        let request = RequestBuilder::new(
+            self.shared.transport.clone(),
+            Method::POST,
+            &format!("{}{}/ping", self.shared.base_url, self.shared.api_path),
+        )
+        .rate_limiter(self.shared.rate_limiter.clone())
+        .header("Cookie", auth.auth_cookies)
+        .header("Authorization", format!("Basic {}", auth.session_id))
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .body(params)
+        .send()
+        .await?;
 
-        let text = request.cc_parse().await?;
-        let data: Session = serde_json::from_str(&text)?;
+        let text = Client::parse_response(request).await?;
+        let data: PingResponse = serde_json::from_str(&text)?;
 
         let session_id = data.meta.session_id;
 
-        self.session_id = session_id.clone();
-        self.last_session_id_updated = Utc::now();
+        let mut auth = self.auth_mut();
+        auth.session_id = session_id.clone();
+        auth.last_session_id_updated = Utc::now();
 
         return Ok(session_id);
     }
@@ -224,75 +710,134 @@ impl Client {
         auth_cookies: String,
         session_id: String,
     ) -> Client {
-        return Client {
+        return Client::from_parts(
+            Arc::new(ReqwestTransport::default()),
             student_id,
             base_url,
-            reqwest_client: reqwest::Client::builder()
-                .redirect(reqwest::redirect::Policy::none())
-                .build()
-                .unwrap(),
-            last_session_id_updated: Utc::now(),
+            STUDENT_API_PATH.to_string(),
             auth_cookies,
             session_id,
+            RetryPolicy::default(),
+            None,
+        );
+    }
+
+    pub(crate) fn set_last_session_id_updated(&self, last_session_id_updated: DateTime<Utc>) {
+        self.auth_mut().last_session_id_updated = last_session_id_updated;
+    }
+
+    pub(crate) fn set_credentials(&self, credentials: LoginCredentials) {
+        self.auth_mut().credentials = Some(credentials);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn from_parts(
+        transport: Arc<dyn Transport>,
+        student_id: String,
+        base_url: String,
+        api_path: String,
+        auth_cookies: String,
+        session_id: String,
+        retry_policy: RetryPolicy,
+        rate_limit: Option<RateLimit>,
+    ) -> Client {
+        return Client {
+            student_id,
+            shared: Arc::new(SharedState {
+                transport,
+                base_url,
+                api_path,
+                auth: RwLock::new(AuthState {
+                    session_id,
+                    auth_cookies,
+                    last_session_id_updated: Utc::now(),
+                    session_refresh_interval: DEFAULT_SESSION_REFRESH_INTERVAL,
+                    credentials: None,
+                }),
+                retry_policy,
+                rate_limiter: rate_limit.map(|limit| Arc::new(RateLimiter::new(limit))),
+                refresh_lock: tokio::sync::Mutex::new(()),
+            }),
         };
     }
 
-    /// This creates a ClassCharts Student Client. It accepts a `code`, `dob` (Date of birth) and an
-    /// optional `base_url`, which should **rarely** be used and is only implimented for testing.
+    /// Exports the current session so it can be saved and later restored with `from_session`.
+    pub fn export_session(&self) -> SessionState {
+        let auth = self.auth();
+
+        return SessionState {
+            base_url: self.shared.base_url.clone(),
+            api_path: self.shared.api_path.clone(),
+            student_id: self.student_id.clone(),
+            session_id: auth.session_id,
+            auth_cookies: auth.auth_cookies,
+            last_session_id_updated: auth.last_session_id_updated,
+        };
+    }
+
+    /// Restores a client from a `SessionState` created by `export_session`.
+    /// The session is validated with a `/ping`, which also refreshes the `session_id`.
     ///
-    /// The `dob` parameter should be in the format of `DD/MM/YYYY`.
+    /// If ClassCharts no longer accepts the remember me cookie then this will return
+    /// `ClientCreationError::SessionExpiredError` and you will need to log in again.
     ///
     /// Example:
     /// ```rust,no_run
     /// use classcharts::Client;
     /// # #[tokio::main]
     /// # async fn main() {
-    /// let mut client = Client::create("your access code", "your date of birth
-    /// (DD/MM/YYYY)", None).await.unwrap();
+    /// # let client = Client::create("your access code", "your date of birth
+    /// # (DD/MM/YYYY)", None).await.unwrap();
+    /// let session = serde_json::to_string(&client.export_session()).unwrap();
+    ///
+    /// // ...later, in another process
+    /// let client = Client::from_session(serde_json::from_str(&session).unwrap()).await.unwrap();
     /// # }
     /// ```
-    pub async fn create<C, D>(
-        code: C,
-        dob: D,
-        base_url: Option<String>,
-    ) -> Result<Self, ClientCreationError>
-    where
-        C: ToString,
-        D: Into<Cow<'static, str>>,
-    {
-        let reqwest_client = reqwest::Client::builder()
-            .redirect(reqwest::redirect::Policy::none())
-            .build()?;
-        let base_url = base_url.unwrap_or("https://www.classcharts.com".to_string());
-
-        let login_form = reqwest::multipart::Form::new()
-            .text("_method", "POST")
-            .text("code", code.to_string().to_uppercase())
-            .text("dob", dob)
-            .text("remember_me", "1")
-            .text("recaptcha-token", "no-token-available");
-
-        let login_request = reqwest_client
-            .post(format!("{}/student/login", base_url))
-            .multipart(login_form)
-            .send()
-            .await?;
+    pub async fn from_session(state: SessionState) -> Result<Self, ClientCreationError> {
+        return ClientBuilder::new().restore_session(state).await;
+    }
 
-        let mut cookies = login_request.cookies();
-        let headers = login_request.headers();
-        let status = login_request.status();
+    /// Logs in to ClassCharts and pulls the session id out of the session cookie.
+    /// Returns the session id and the cookies which should be sent with every request.
+    pub(crate) async fn login(
+        transport: &Arc<dyn Transport>,
+        base_url: &str,
+        credentials: &LoginCredentials,
+    ) -> Result<(String, String), ClientCreationError> {
+        let login_form = credentials.login_form();
+
+        let login_response = RequestBuilder::new(
+            transport.clone(),
+            Method::POST,
+            &format!("{}{}", base_url, credentials.login_path()),
+        )
+        .header(reqwest::header::CONTENT_TYPE, login_form.content_type())
+        .body(login_form.finish())
+        .send()
+        .await?;
+
+        let headers = login_response.headers;
+        let status = login_response.status;
 
         if status != 302 || headers.get("set-cookie").is_none() {
             return Err(ClientCreationError::AuthenticationError);
         }
 
-        let session_cookie = cookies
-            .find(|cookie| cookie.name() == "student_session_credentials")
+        let mut cookies = headers
+            .get_all("set-cookie")
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|header| header.split(';').next())
+            .filter_map(|cookie| cookie.trim().split_once('='));
+
+        let (_, session_cookie) = cookies
+            .find(|(name, _)| *name == credentials.cookie_name())
             .ok_or(())
             .map_err(ClientCreationError::MissingSesssionCookie)?;
 
         // i don't think we actually need this
-        let session_cookie = urlencoding::decode(session_cookie.value())?;
+        let session_cookie = urlencoding::decode(session_cookie)?;
 
         let session_id = serde_json::from_str::<SessionCookie>(&session_cookie)?.session_id;
 
@@ -300,27 +845,49 @@ impl Client {
             .get("set-cookie")
             .unwrap()
             .to_str()?
-            .split(",")
+            .split(',')
             .collect::<Vec<&str>>()
             .join(";");
 
-        let mut client = Client {
-            session_id,
-            student_id: "".to_string(),
-            reqwest_client,
-            base_url,
-            auth_cookies,
-            last_session_id_updated: Utc::now(),
-        };
+        return Ok((session_id, auth_cookies));
+    }
 
-        let cc_response = client
-            .get_student_info()
-            .await
-            .map_err(ClientCreationError::ApiRequestError)?;
+    /// This creates a ClassCharts Student Client. It accepts a `code`, `dob` (Date of birth) and an
+    /// optional `base_url`, which should **rarely** be used and is only implimented for testing.
+    /// This is a shortcut for `ClientBuilder::new().login(code, dob)`.
+    ///
+    /// The `dob` parameter should be in the format of `DD/MM/YYYY`.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// use classcharts::Client;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::create("your access code", "your date of birth
+    /// (DD/MM/YYYY)", None).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn create<C, D>(
+        code: C,
+        dob: D,
+        base_url: Option<String>,
+    ) -> Result<Self, ClientCreationError>
+    where
+        C: ToString,
+        D: Into<Cow<'static, str>>,
+    {
+        let mut builder = ClientBuilder::new();
 
-        client.student_id = cc_response.data.user.id.to_string();
+        if let Some(base_url) = base_url {
+            builder = builder.base_url(base_url);
+        }
 
-        return Ok(client);
+        return builder.login(code, dob).await;
+    }
+
+    /// Creates a `ClientBuilder`, which can configure timeouts, a proxy, etc. before logging in.
+    pub fn builder() -> ClientBuilder {
+        return ClientBuilder::new();
     }
 }
 
@@ -453,9 +1020,358 @@ mod tests {
             .unwrap();
 
         assert_eq!(client.student_id, "3949234");
-        assert_eq!(client.session_id, "jf99rm23pdi29dj32fh23i");
+        assert_eq!(client.session_id(), "jf99rm23pdi29dj32fh23i");
 
         student_login_response.assert();
         student_info_response.assert();
     }
+
+    #[tokio::test]
+    async fn session_roundtrip_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST)
+                .path("/apiv2student/ping")
+                .header("Cookie", "auth_cookies");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {},
+                    "meta": {
+                        "session_id": "new_session_id",
+                        "version": "27.16.2",
+                    },
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let state = serde_json::to_string(&client.export_session()).unwrap();
+        let state: SessionState = serde_json::from_str(&state).unwrap();
+
+        let client = Client::from_session(state).await.unwrap();
+
+        assert_eq!(client.student_id, "student_id");
+        assert_eq!(client.session_id(), "new_session_id");
+
+        ping_response.assert();
+    }
+
+    #[tokio::test]
+    async fn expired_session_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 0,
+                    "error": "Session expired",
+                }));
+        });
+
+        let state = Client::generate_mock(server.base_url()).export_session();
+
+        let err = Client::from_session(state).await.unwrap_err();
+
+        assert!(matches!(err, ClientCreationError::SessionExpiredError(_)));
+
+        ping_response.assert();
+    }
+
+    #[tokio::test]
+    async fn session_refresh_interval_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {},
+                    "meta": {
+                        "session_id": "new_session_id",
+                    },
+                }));
+        });
+
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic new_session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "",
+                        "fields": []
+                    },
+                    "meta": [],
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        // the session is from yesterday, so comparing only the time of day would not refresh it
+        client.set_last_session_id_updated(
+            Utc::now() - chrono::Duration::days(1) + chrono::Duration::minutes(1),
+        );
+
+        let _ = client.get_pupilfields().await.unwrap();
+
+        assert_eq!(client.session_id(), "new_session_id");
+
+        ping_response.assert();
+        pupilfields_response.assert();
+    }
+
+    #[tokio::test]
+    async fn expired_session_replay_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let expired_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic session_id");
+            then.status(401);
+        });
+
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {},
+                    "meta": {
+                        "session_id": "new_session_id",
+                    },
+                }));
+        });
+
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic new_session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "",
+                        "fields": []
+                    },
+                    "meta": [],
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+        client.set_session_refresh_interval(Duration::from_secs(60 * 60));
+
+        let _ = client.get_pupilfields().await.unwrap();
+
+        expired_response.assert();
+        ping_response.assert();
+        pupilfields_response.assert();
+    }
+
+    #[tokio::test]
+    async fn expired_session_relogin_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let expired_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 0,
+                    "error": "Session expired",
+                }));
+        });
+
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(401);
+        });
+
+        let student_login_response = server.mock(|when, then| {
+            when.method(POST).path("/student/login");
+            then.status(302).header(
+                "set-cookie",
+                "student_session_credentials={\"session_id\":\"new_session_id\"}",
+            );
+        });
+
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic new_session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "",
+                        "fields": []
+                    },
+                    "meta": [],
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+        client.set_credentials(LoginCredentials::Student {
+            code: "my_code".to_string(),
+            dob: "my_dob".to_string(),
+        });
+
+        let _ = client.get_pupilfields().await.unwrap();
+
+        expired_response.assert();
+        ping_response.assert();
+        student_login_response.assert();
+        pupilfields_response.assert();
+    }
+
+    #[tokio::test]
+    async fn session_refresh_relogin_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(401);
+        });
+
+        let student_login_response = server.mock(|when, then| {
+            when.method(POST).path("/student/login");
+            then.status(302).header(
+                "set-cookie",
+                "student_session_credentials={\"session_id\":\"new_session_id\"}",
+            );
+        });
+
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic new_session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "",
+                        "fields": []
+                    },
+                    "meta": [],
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+        client.set_credentials(LoginCredentials::Student {
+            code: "my_code".to_string(),
+            dob: "my_dob".to_string(),
+        });
+        client.set_last_session_id_updated(Utc::now() - chrono::Duration::days(1));
+
+        let _ = client.get_pupilfields().await.unwrap();
+
+        assert_eq!(client.session_id(), "new_session_id");
+
+        ping_response.assert();
+        student_login_response.assert();
+        pupilfields_response.assert();
+    }
+
+    #[test]
+    fn is_session_expired_test() {
+        assert!(ErrorResponse::SessionExpiredError.is_session_expired());
+        assert!(
+            ErrorResponse::ClassChartsError(0, "Session expired".to_string()).is_session_expired()
+        );
+        assert!(!ErrorResponse::ClassChartsError(
+            0,
+            "The homework submission deadline has expired".to_string()
+        )
+        .is_session_expired());
+    }
+
+    #[test]
+    fn client_is_shareable_test() {
+        fn assert_shareable<T: Clone + Send + Sync>() {}
+
+        assert_shareable::<Client>();
+    }
+
+    #[tokio::test]
+    async fn concurrent_refresh_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2student/ping");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(100))
+                .json_body(json!({
+                    "success": 1,
+                    "data": {},
+                    "meta": {
+                        "session_id": "new_session_id",
+                    },
+                }));
+        });
+
+        let pupilfields_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/customfields/student_id")
+                .header("Authorization", "Basic new_session_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {
+                        "note": "",
+                        "fields": []
+                    },
+                    "meta": [],
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+        client.set_last_session_id_updated(Utc::now() - chrono::Duration::hours(1));
+
+        let other_client = client.clone();
+        let task = tokio::spawn(async move { other_client.get_pupilfields().await.map(|_| ()) });
+
+        let (first, second, third) = tokio::join!(
+            client.get_pupilfields(),
+            client.get_pupilfields(),
+            task,
+        );
+        first.unwrap();
+        second.unwrap();
+        third.unwrap().unwrap();
+
+        ping_response.assert_hits(1);
+        pupilfields_response.assert_hits(3);
+    }
 }
@@ -0,0 +1,624 @@
+//! Exports to iCalendar ([RFC 5545](https://datatracker.ietf.org/doc/html/rfc5545)) files, which
+//! can be imported into most calendar apps.
+//!
+//! This is only available with the `ics` feature. Every event has a stable `UID`, so importing a
+//! newer export of the same dates updates the existing events rather than duplicating them. Times
+//! are written in UK local time with the `Europe/London` timezone.
+//!
+//! Lessons and detentions are written as events, and homework as todos due on the `due_date`.
+//! Anything implementing `ToIcs` can be added to a `Calendar`:
+//!
+//! ```rust,no_run
+//! # use classcharts::{Client, ics::Calendar};
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let client = Client::create("", "", None).await.unwrap();
+//! let mut calendar = Calendar::new().name("School");
+//! calendar.extend(&client.get_homeworks(None).await.unwrap().data);
+//! calendar.extend(&client.get_detentions().await.unwrap().data);
+//! # }
+//! ```
+//!
+//! ```rust,no_run
+//! # use classcharts::Client;
+//! # use chrono::NaiveDate;
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let client = Client::create("", "", None).await.unwrap();
+//! let calendar = client
+//!     .get_timetable_calendar(
+//!         NaiveDate::from_ymd_opt(2023, 9, 25).unwrap(),
+//!         NaiveDate::from_ymd_opt(2023, 9, 29).unwrap(),
+//!     )
+//!     .await
+//!     .unwrap();
+//!
+//! std::fs::write("timetable.ics", calendar.to_ics()).unwrap();
+//! # }
+//! ```
+
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+
+use crate::{
+    api::{
+        detentions::Detention,
+        homework::{Homework, HomeworkState},
+        lessons::Lesson,
+    },
+    client::{Client, ErrorResponse},
+    text::html_to_text,
+};
+
+const PRODUCT_ID: &str = "-//classcharts-rs//classcharts//EN";
+
+const TIMEZONE_ID: &str = "Europe/London";
+
+const TIMEZONE: &str = "BEGIN:VTIMEZONE\r\n\
+    TZID:Europe/London\r\n\
+    BEGIN:DAYLIGHT\r\n\
+    TZOFFSETFROM:+0000\r\n\
+    TZOFFSETTO:+0100\r\n\
+    TZNAME:BST\r\n\
+    DTSTART:19700329T010000\r\n\
+    RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n\
+    END:DAYLIGHT\r\n\
+    BEGIN:STANDARD\r\n\
+    TZOFFSETFROM:+0100\r\n\
+    TZOFFSETTO:+0000\r\n\
+    TZNAME:GMT\r\n\
+    DTSTART:19701025T020000\r\n\
+    RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+    END:STANDARD\r\n\
+    END:VTIMEZONE\r\n";
+
+/// Something which can be written as an iCalendar component, like a `VEVENT` or `VTODO`.
+pub trait ToIcs {
+    /// The iCalendar component, with `\r\n` line endings, or `None` if it can't be placed on a
+    /// calendar, e.g. a detention which hasn't been given a date.
+    fn to_ics(&self) -> Option<String>;
+}
+
+/// An iCalendar `VCALENDAR`.
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    name: Option<String>,
+    components: Vec<String>,
+}
+
+impl Calendar {
+    pub fn new() -> Calendar {
+        return Calendar::default();
+    }
+
+    /// The name calendar apps show for the calendar.
+    pub fn name<T>(mut self, name: T) -> Calendar
+    where
+        T: ToString,
+    {
+        self.name = Some(name.to_string());
+        return self;
+    }
+
+    /// Adds an event (or todo) to the calendar. Items without a date are skipped.
+    pub fn add<T>(&mut self, item: &T)
+    where
+        T: ToIcs + ?Sized,
+    {
+        if let Some(component) = item.to_ics() {
+            self.components.push(component);
+        }
+    }
+
+    /// Adds every item to the calendar.
+    pub fn extend<'a, T, I>(&mut self, items: I)
+    where
+        T: ToIcs + 'a,
+        I: IntoIterator<Item = &'a T>,
+    {
+        for item in items {
+            self.add(item);
+        }
+    }
+
+    /// The number of events (and todos) in the calendar.
+    pub fn len(&self) -> usize {
+        return self.components.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.components.is_empty();
+    }
+
+    /// The contents of the `.ics` file.
+    pub fn to_ics(&self) -> String {
+        let mut calendar = Component::new("VCALENDAR");
+        calendar.raw("VERSION", "2.0");
+        calendar.raw("PRODID", PRODUCT_ID);
+        calendar.raw("CALSCALE", "GREGORIAN");
+        calendar.raw("METHOD", "PUBLISH");
+        calendar.raw("X-WR-TIMEZONE", TIMEZONE_ID);
+
+        if let Some(name) = &self.name {
+            calendar.text("X-WR-CALNAME", name);
+        }
+
+        calendar.nested(TIMEZONE);
+
+        for component in &self.components {
+            calendar.nested(component);
+        }
+
+        return calendar.finish();
+    }
+}
+
+impl fmt::Display for Calendar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(&self.to_ics());
+    }
+}
+
+impl ToIcs for Lesson {
+    fn to_ics(&self) -> Option<String> {
+        let summary = if self.subject_name.is_empty() {
+            &self.lesson_name
+        } else {
+            &self.subject_name
+        };
+
+        let mut description = vec![];
+
+        if !self.teacher_name.is_empty() {
+            description.push(format!("Teacher: {}", self.teacher_name));
+        }
+        if !self.period_number.is_empty() {
+            description.push(format!("Period: {}", self.period_number));
+        }
+        for note in [&self.note, &self.pupil_note] {
+            let note = html_to_text(note);
+
+            if !note.is_empty() {
+                description.push(note);
+            }
+        }
+
+        let mut event = Component::new("VEVENT");
+        event.raw("UID", &format!("lesson-{}-{}@classcharts", self.key, self.date));
+        event.stamp();
+        event.local_datetime("DTSTART", self.start_time);
+        event.local_datetime("DTEND", self.end_time);
+        event.text("SUMMARY", summary);
+
+        if !self.room_name.is_empty() {
+            event.text("LOCATION", &self.room_name);
+        }
+        if !description.is_empty() {
+            event.text("DESCRIPTION", &description.join("\n\n"));
+        }
+
+        return Some(event.finish());
+    }
+}
+
+/// A `VTODO` due on the `due_date`, which is completed once the homework is ticked or handed in.
+impl ToIcs for Homework {
+    fn to_ics(&self) -> Option<String> {
+        let completed = self.status.ticked
+            || matches!(
+                self.status.state,
+                Some(HomeworkState::Completed | HomeworkState::Late)
+            );
+
+        let mut description = vec![];
+
+        if !self.teacher.is_empty() {
+            description.push(format!("Teacher: {}", self.teacher));
+        }
+
+        let text = html_to_text(&self.description);
+
+        if !text.is_empty() {
+            description.push(text);
+        }
+
+        let mut todo = Component::new("VTODO");
+        todo.raw("UID", &format!("homework-{}@classcharts", self.id));
+        todo.stamp();
+        todo.date("DTSTART", self.issue_date);
+        todo.date("DUE", self.due_date);
+        todo.text("SUMMARY", &format!("{}: {}", self.subject, self.title));
+        todo.text("CATEGORIES", &self.subject);
+
+        if !description.is_empty() {
+            todo.text("DESCRIPTION", &description.join("\n\n"));
+        }
+
+        if completed {
+            todo.raw("STATUS", "COMPLETED");
+            todo.raw("PERCENT-COMPLETE", "100");
+        } else {
+            todo.raw("STATUS", "NEEDS-ACTION");
+        }
+
+        return Some(todo.finish());
+    }
+}
+
+/// A `VEVENT` lasting `length` minutes, or an all day event if the detention has no `time`.
+impl ToIcs for Detention {
+    fn to_ics(&self) -> Option<String> {
+        let date = self.date?;
+
+        let mut description = vec![];
+
+        if !self.lesson_pupil_behaviour.reason.is_empty() {
+            description.push(format!("Reason: {}", self.lesson_pupil_behaviour.reason));
+        }
+        if let Some(lesson) = &self.lesson {
+            description.push(format!("Lesson: {} ({})", lesson.name, lesson.subject.name));
+        }
+        if let Some(teacher) = &self.teacher {
+            description.push(format!(
+                "Teacher: {} {} {}",
+                teacher.title, teacher.first_name, teacher.last_name
+            ));
+        }
+        if let Some(notes) = self.notes.as_deref().filter(|notes| !notes.is_empty()) {
+            description.push(notes.to_string());
+        }
+
+        let mut event = Component::new("VEVENT");
+        event.raw("UID", &format!("detention-{}@classcharts", self.id));
+        event.stamp();
+
+        match self.time {
+            Some(time) => {
+                event.local_datetime("DTSTART", date.and_time(time));
+
+                if let Some(length) = self.length {
+                    event.raw("DURATION", &format!("PT{}M", length));
+                }
+            }
+            None => {
+                event.date("DTSTART", date);
+                event.date("DTEND", date.succ_opt()?);
+            }
+        }
+
+        event.text("SUMMARY", &self.detention_type.name);
+
+        if let Some(location) = self.location.as_deref().filter(|location| !location.is_empty()) {
+            event.text("LOCATION", location);
+        }
+        if !description.is_empty() {
+            event.text("DESCRIPTION", &description.join("\n"));
+        }
+
+        return Some(event.finish());
+    }
+}
+
+impl Client {
+    /// Gets the current student's lessons between two dates (inclusive) as a `Calendar`.
+    /// See `Client::get_timetable`.
+    pub async fn get_timetable_calendar(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Calendar, ErrorResponse> {
+        let timetable = self.get_timetable(from, to).await?;
+
+        let mut calendar = Calendar::new().name("Timetable");
+        calendar.extend(timetable.lessons());
+
+        return Ok(calendar);
+    }
+}
+
+/// Writes the lines of an iCalendar component.
+pub(crate) struct Component {
+    name: &'static str,
+    lines: String,
+}
+
+impl Component {
+    pub(crate) fn new(name: &'static str) -> Component {
+        return Component {
+            name,
+            lines: format!("BEGIN:{}\r\n", name),
+        };
+    }
+
+    /// Adds a property whose value is written as is.
+    pub(crate) fn raw(&mut self, name: &str, value: &str) {
+        self.lines.push_str(&fold_line(&format!("{}:{}", name, value)));
+    }
+
+    /// Adds a text property, escaping its value.
+    pub(crate) fn text(&mut self, name: &str, value: &str) {
+        self.raw(name, &escape_text(value));
+    }
+
+    /// Adds a date and time in UK local time.
+    pub(crate) fn local_datetime(&mut self, name: &str, value: NaiveDateTime) {
+        self.raw(
+            &format!("{};TZID={}", name, TIMEZONE_ID),
+            &value.format("%Y%m%dT%H%M%S").to_string(),
+        );
+    }
+
+    /// Adds a date without a time.
+    pub(crate) fn date(&mut self, name: &str, value: NaiveDate) {
+        self.raw(
+            &format!("{};VALUE=DATE", name),
+            &value.format("%Y%m%d").to_string(),
+        );
+    }
+
+    /// Adds the `DTSTAMP`, which is required for every event.
+    pub(crate) fn stamp(&mut self) {
+        self.raw("DTSTAMP", &Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+    }
+
+    /// Adds a component which has already been written.
+    pub(crate) fn nested(&mut self, component: &str) {
+        self.lines.push_str(component);
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.lines.push_str(&format!("END:{}\r\n", self.name));
+        return self.lines;
+    }
+}
+
+fn escape_text(value: &str) -> String {
+    return value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n");
+}
+
+/// Folds a content line so no line is longer than 75 octets, as required by RFC 5545.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut length = 0;
+
+    for char in line.chars() {
+        if length + char.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(char);
+        length += char.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+
+    return folded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn lesson_json(date: &str, key: usize) -> serde_json::Value {
+        return json!({
+            "teacher_name": "Mr Smith",
+            "lesson_name": "10A/Ma1",
+            "subject_name": "Maths",
+            "is_alternative_lesson": false,
+            "period_name": "1Mon:P1",
+            "period_number": "P1",
+            "room_name": "M4",
+            "date": date,
+            "start_time": format!("{}T08:45:00+01:00", date),
+            "end_time": format!("{}T09:45:00+01:00", date),
+            "key": key,
+            "note_abstract": "",
+            "note": "<p>Bring a calculator, ruler; and protractor</p>",
+            "pupil_note_abstract": "",
+            "pupil_note": "",
+            "pupil_note_raw": ""
+        });
+    }
+
+    #[test]
+    fn lesson_to_ics_test() {
+        let lesson: Lesson = serde_json::from_value(lesson_json("2023-09-25", 12)).unwrap();
+        let event = lesson.to_ics().unwrap();
+
+        assert!(event.starts_with("BEGIN:VEVENT\r\nUID:lesson-12-2023-09-25@classcharts\r\n"));
+        assert!(event.contains("\r\nDTSTART;TZID=Europe/London:20230925T084500\r\n"));
+        assert!(event.contains("\r\nDTEND;TZID=Europe/London:20230925T094500\r\n"));
+        assert!(event.contains("\r\nSUMMARY:Maths\r\n"));
+        assert!(event.contains("\r\nLOCATION:M4\r\n"));
+        assert!(event.contains(
+            "\r\nDESCRIPTION:Teacher: Mr Smith\\n\\nPeriod: P1\\n\\nBring a calculator\\, ruler\\;\r\n  and protractor\r\n"
+        ));
+        assert!(event.ends_with("END:VEVENT\r\n"));
+    }
+
+    #[test]
+    fn homework_to_ics_test() {
+        let mut homework: Homework = serde_json::from_value(json!({
+            "lesson": "10A/Ma1",
+            "subject": "Maths",
+            "teacher": "Mr Smith",
+            "homework_type": "Homework",
+            "id": 7,
+            "title": "Fractions",
+            "meta_title": "",
+            "description": "<p>Page 4 &amp; 5</p>",
+            "issue_date": "2023-09-16",
+            "due_date": "2023-09-19",
+            "completion_time_unit": "minutes",
+            "completion_time_value": "30",
+            "publish_time": "00:00:00",
+            "status": {
+                "id": 70,
+                "state": "not_completed",
+                "mark": null,
+                "mark_relative": 0,
+                "ticked": "no",
+                "allow_attachments": false,
+                "first_seen_date": null,
+                "last_seen_date": null,
+                "attachments": [],
+                "has_feedback": false
+            },
+            "validated_links": [],
+            "validated_attachments": []
+        }))
+        .unwrap();
+
+        let todo = homework.to_ics().unwrap();
+
+        assert!(todo.starts_with("BEGIN:VTODO\r\nUID:homework-7@classcharts\r\n"));
+        assert!(todo.contains("\r\nDTSTART;VALUE=DATE:20230916\r\n"));
+        assert!(todo.contains("\r\nDUE;VALUE=DATE:20230919\r\n"));
+        assert!(todo.contains("\r\nSUMMARY:Maths: Fractions\r\n"));
+        assert!(todo.contains("\r\nDESCRIPTION:Teacher: Mr Smith\\n\\nPage 4 & 5\r\n"));
+        assert!(todo.contains("\r\nSTATUS:NEEDS-ACTION\r\n"));
+
+        homework.status.state = Some(HomeworkState::Late);
+        let todo = homework.to_ics().unwrap();
+
+        assert!(todo.contains("\r\nSTATUS:COMPLETED\r\nPERCENT-COMPLETE:100\r\n"));
+    }
+
+    #[test]
+    fn detention_to_ics_test() {
+        let mut detention: Detention = serde_json::from_value(json!({
+            "id": 3,
+            "attended": "pending",
+            "date": "2023-08-24:00:00+00:00",
+            "length": 30,
+            "location": "Room 1",
+            "notes": null,
+            "time": "16:00",
+            "pupil": {
+                "id": 1,
+                "first_name": "first_name",
+                "last_name": "last_name",
+                "school": {
+                    "opt_notes_names": "yes",
+                    "opt_notes_comments": "no",
+                    "opt_notes_comments_pupils": "no"
+                }
+            },
+            "lesson": null,
+            "lesson_pupil_behaviour": {
+                "reason": "Late to lesson"
+            },
+            "teacher": null,
+            "detention_type": {
+                "name": "After school detention"
+            }
+        }))
+        .unwrap();
+
+        let event = detention.to_ics().unwrap();
+
+        assert!(event.starts_with("BEGIN:VEVENT\r\nUID:detention-3@classcharts\r\n"));
+        assert!(event.contains("\r\nDTSTART;TZID=Europe/London:20230824T160000\r\nDURATION:PT30M\r\n"));
+        assert!(event.contains("\r\nSUMMARY:After school detention\r\n"));
+        assert!(event.contains("\r\nLOCATION:Room 1\r\n"));
+        assert!(event.contains("\r\nDESCRIPTION:Reason: Late to lesson\r\n"));
+
+        detention.time = None;
+        let event = detention.to_ics().unwrap();
+
+        assert!(event.contains("\r\nDTSTART;VALUE=DATE:20230824\r\nDTEND;VALUE=DATE:20230825\r\n"));
+
+        detention.date = None;
+        assert_eq!(detention.to_ics(), None);
+
+        let mut calendar = Calendar::new();
+        calendar.add(&detention);
+        assert!(calendar.is_empty());
+    }
+
+    #[test]
+    fn fold_line_test() {
+        let line = "é".repeat(50);
+        let folded = fold_line(&line);
+
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[tokio::test]
+    async fn get_timetable_calendar_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let monday_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/timetable/student_id")
+                .query_param("date", "2023-09-25");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [lesson_json("2023-09-25", 1)],
+                    "meta": {
+                        "dates": ["2023-09-25"],
+                        "timetable_dates": ["2023-09-25", "2023-09-27"],
+                        "start_time": "2023-09-25T00:00:00+00:00",
+                        "end_time": "2023-09-25T23:59:59+00:00"
+                    }
+                }));
+        });
+
+        let wednesday_response = server.mock(|when, then| {
+            when.method(GET)
+                .path("/apiv2student/timetable/student_id")
+                .query_param("date", "2023-09-27");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [lesson_json("2023-09-27", 2)],
+                    "meta": {
+                        "dates": ["2023-09-27"],
+                        "timetable_dates": ["2023-09-25", "2023-09-27"],
+                        "start_time": "2023-09-27T00:00:00+00:00",
+                        "end_time": "2023-09-27T23:59:59+00:00"
+                    }
+                }));
+        });
+
+        let client = Client::generate_mock(server.base_url());
+
+        let calendar = client
+            .get_timetable_calendar(
+                NaiveDate::from_ymd_opt(2023, 9, 25).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 9, 27).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let ics = calendar.to_ics();
+
+        assert_eq!(calendar.len(), 2);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("\r\nBEGIN:VTIMEZONE\r\nTZID:Europe/London\r\n"));
+        assert!(ics.contains("\r\nUID:lesson-1-2023-09-25@classcharts\r\n"));
+        assert!(ics.contains("\r\nUID:lesson-2-2023-09-27@classcharts\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+
+        // the tuesday isn't a timetable date, so isn't fetched
+        monday_response.assert();
+        wednesday_response.assert();
+    }
+}
@@ -11,7 +11,7 @@
 //! ## Prerequisites
 //!
 //! * A ClassCharts Access Code (provided by your school). This is NOT saved or sent to anywhere
-//! but ClassChart's servers.
+//!   but ClassChart's servers.
 //!
 //! ## Usage
 //!
@@ -21,7 +21,7 @@
 //! use classcharts::Client;
 //! # #[tokio::main]
 //! # async fn main() {
-//! let mut client = Client::create("your access code", "your date of birth
+//! let client = Client::create("your access code", "your date of birth
 //! (DD/MM/YYYY)", None).await.unwrap();
 //!
 //! let student_info = client.get_student_info().await.unwrap();
@@ -35,7 +35,7 @@
 //! # use classcharts::Client;
 //! # #[tokio::main]
 //! # async fn main() {
-//! # let mut client = Client::create("your access code", "your date of birth
+//! # let client = Client::create("your access code", "your date of birth
 //! # (DD/MM/YYYY)", None).await.unwrap();
 //! let homework = client.get_homeworks(None).await.unwrap();
 //! # }
@@ -50,7 +50,18 @@
 //! * `get_behaviour`
 //! * `get_detentions`
 //! * `get_homeworks`
+//! * `get_homework`
+//! * `query_homeworks`
+//! * `tick_homework`
+//! * `upload_homework_attachment`
+//! * `delete_homework_attachment`
+//! * `download_attachment`
+//! * `save_attachments`
 //! * `get_lessons`
+//! * `get_timetable`
+//! * `set_lesson_note`
+//! * `clear_lesson_note`
+//! * `get_timetable_calendar` (with the `ics` feature)
 //! * `get_pupilfields`
 //! * `get_rewards`
 //! * `purchase_reward`
@@ -58,6 +69,114 @@
 //!
 //! They will all return a `Result<SuccessResponse, ErrorResponse>`.
 //!
+//! ## Configuring the client
+//!
+//! `ClientBuilder` configures timeouts, the user agent, a proxy, default headers and the session
+//! refresh interval, or accepts a pre-built `reqwest::Client`, before logging in.
+//!
+//! ```rust,no_run
+//! use classcharts::ClientBuilder;
+//! use std::time::Duration;
+//! # #[tokio::main]
+//! # async fn main() {
+//! let client = ClientBuilder::new()
+//!     .connect_timeout(Duration::from_secs(5))
+//!     .timeout(Duration::from_secs(30))
+//!     .user_agent("my-app/1.0")
+//!     .login("your access code", "your date of birth (DD/MM/YYYY)")
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! ## Retries and rate limiting
+//!
+//! `GET` requests which fail to send, or which ClassCharts answers with `429` or a `5xx` status,
+//! are retried up to 3 times with exponential backoff, honouring any `Retry-After` header. This is
+//! configured with `ClientBuilder::retry_policy`, and `ClientBuilder::rate_limit` limits how many
+//! requests are sent. `purchase_reward` is never retried.
+//!
+//! ```rust,no_run
+//! use classcharts::{ClientBuilder, RateLimit, RetryPolicy};
+//! # #[tokio::main]
+//! # async fn main() {
+//! let client = ClientBuilder::new()
+//!     .retry_policy(RetryPolicy { max_attempts: 5, ..RetryPolicy::default() })
+//!     .rate_limit(RateLimit::per_second(2))
+//!     .login("your access code", "your date of birth (DD/MM/YYYY)")
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! ## Blocking client
+//!
+//! With the `blocking` feature enabled, `classcharts::blocking::Client` exposes the same methods
+//! without `async`, for scripts which don't otherwise need an async runtime.
+//!
+//! ```toml
+//! classcharts = { version = "1", features = ["blocking"] }
+//! ```
+//!
+//! ## Rich text
+//!
+//! Homework descriptions, lesson notes, announcements and custom field notes are HTML. With the
+//! `text` feature enabled, they can be converted to plain text or Markdown with accessors like
+//! `Homework::description_text` and `Homework::description_markdown`, or with the functions in
+//! `classcharts::text`.
+//!
+//! ## Calendars
+//!
+//! With the `ics` feature enabled, `Client::get_timetable_calendar` exports the timetable as an
+//! iCalendar file, which can be imported into most calendar apps. Homework due dates and
+//! detentions can be added to a calendar too, see `classcharts::ics`.
+//!
+//! ## Custom transports
+//!
+//! Requests are sent through a `Transport`, which defaults to `ReqwestTransport`. A custom
+//! transport can be passed to `ClientBuilder::transport`, for example an in-memory fake in your
+//! own unit tests, or middleware which wraps `ReqwestTransport`.
+//!
+//! ## Saving sessions
+//!
+//! Logging in on every process start is slow, so a session can be exported with
+//! `Client::export_session` and restored later with `Client::from_session`. The `SessionState` is
+//! serializable with `serde`.
+//!
+//! ```rust,no_run
+//! # use classcharts::Client;
+//! # #[tokio::main]
+//! # async fn main() {
+//! # let client = Client::create("your access code", "your date of birth
+//! # (DD/MM/YYYY)", None).await.unwrap();
+//! let session = serde_json::to_string(&client.export_session()).unwrap();
+//!
+//! let client = Client::from_session(serde_json::from_str(&session).unwrap()).await.unwrap();
+//! # }
+//! ```
+//!
+//! ## Parent accounts
+//!
+//! Parents log in with their email and password using a `ParentClient`. The pupils linked to the
+//! account are fetched on login and the first one is selected.
+//!
+//! ```rust,no_run
+//! use classcharts::ParentClient;
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut client = ParentClient::create("your email", "your password", None).await.unwrap();
+//!
+//! let pupil_id = client.pupils[0].id;
+//! client.select_pupil(pupil_id).unwrap();
+//!
+//! let homework = client.get_homeworks(None).await.unwrap();
+//! # }
+//! ```
+//!
+//! The `ParentClient` exposes `get_pupils`, `get_homeworks`, `get_behaviour`, `get_activity`,
+//! `get_full_activity`, `get_attendance`, `get_detentions`, `get_lessons` and
+//! `get_announcements`.
+//!
 //! # Responses and Errors
 //!
 //! This library trys to not abstract over the ClassCharts API too much.
@@ -70,6 +189,12 @@
 //! You can find the specfic `Data` / `Meta` under `classcharts::api`, for example
 //! `classcharts::api::homework::HomeworkData`.
 //!
+//! ## Dates and times
+//!
+//! Dates and times are parsed into `chrono::NaiveDate`, `NaiveTime` and `NaiveDateTime`, in UK
+//! local time (`Europe/London`), whichever format ClassCharts returned them in. Use
+//! `classcharts::api::dates::to_uk_datetime` to attach the timezone.
+//!
 //! ## `ErrorResponse` enum
 //!
 //! This will be either:
@@ -78,12 +203,54 @@
 //! * `SerdeJsonParsingError` - serde_json::Error 
 //! * `ClassChartsStatusError` - This will occur when the ClassCharts API returns a non `{ success: 1 }` with no error message attribute
 //! * `ClassChartsError` - Similar to `ClassChartsStatusError`, but it includes the error message attribute ClassCharts returned
+//! * `UnknownPupilError` - A `ParentClient` was asked to select a pupil which isn't linked to the account
+//! * `SessionExpiredError` - ClassCharts rejected the session and it could not be refreshed
+//! * `ReauthenticationError` - ClientError - The session expired and logging in again failed
+//! * `InvalidRequestError` - A request could not be built, e.g. an invalid header value
+//! * `BodyDecodingError` - The response body is not valid UTF-8
+//! * `InvalidAttachmentError` - A homework attachment was rejected before it was uploaded, e.g. its file type isn't allowed
+//! * `FileError` - std::io::Error - An attachment could not be saved
+//! * `HttpStatusError` - ClassCharts kept responding with a retryable status, such as `503`
+//! * `LessonNotesDisabledError` - The school doesn't allow pupils to add notes to lessons
+//! * `UnknownLessonError` - There is no lesson with the given key on the given date
+//! * `TransportError` - Returned by custom `Transport`s when they fail to send a request
+//!
+//! ## Sessions
+//!
+//! ClassCharts `session_id`s are short lived, so the client requests a new one every 3 minutes
+//! (configurable with `Client::set_session_refresh_interval`). If ClassCharts still reports the
+//! session as expired, the client refreshes it (or logs in again with the credentials it was
+//! created with) and replays the request once.
+//!
+//! Every method takes `&self`, and `Client` is `Clone + Send + Sync`. Clones share the same
+//! session, so one client can be used from many tasks at once, and only one of them will refresh
+//! the session when it goes stale.
 
+mod builder;
 mod client;
 mod macros;
+mod multipart;
+mod parent;
+mod retry;
+mod transport;
 
+pub use builder::ClientBuilder;
 pub use client::ErrorResponse;
 pub use client::SuccessResponse;
 pub use client::Client;
 pub use client::ClientCreationError as ClientError;
+pub use client::SessionState;
+pub use client::{Session, SessionMeta};
+pub use client::DEFAULT_SESSION_REFRESH_INTERVAL;
+pub use parent::ParentClient;
+pub use retry::{RateLimit, RetryPolicy};
+pub use transport::{
+    ReqwestTransport, RequestBuilder, Transport, TransportRequest, TransportResponse,
+};
 pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "ics")]
+pub mod ics;
+#[cfg(feature = "text")]
+pub mod text;
@@ -0,0 +1,63 @@
+use crate::retry::random_u64;
+
+/// A minimal `multipart/form-data` encoder, so multipart bodies can be sent through any
+/// `Transport` as plain bytes.
+pub(crate) struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Multipart {
+    pub fn new() -> Multipart {
+        return Multipart {
+            boundary: format!("----classcharts{:016x}", random_u64()),
+            body: vec![],
+        };
+    }
+
+    pub fn text(mut self, name: &str, value: &str) -> Multipart {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                self.boundary,
+                escape_quotes(name),
+                value
+            )
+            .as_bytes(),
+        );
+
+        return self;
+    }
+
+    pub fn file(mut self, name: &str, file_name: &str, content_type: &str, data: &[u8]) -> Multipart {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                self.boundary,
+                escape_quotes(name),
+                escape_quotes(file_name),
+                content_type
+            )
+            .as_bytes(),
+        );
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+
+        return self;
+    }
+
+    pub fn content_type(&self) -> String {
+        return format!("multipart/form-data; boundary={}", self.boundary);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+
+        return self.body;
+    }
+}
+
+fn escape_quotes(value: &str) -> String {
+    return value.replace('"', "%22").replace(['\r', '\n'], "");
+}
@@ -0,0 +1,351 @@
+use chrono::NaiveDate;
+
+use crate::{
+    api::{
+        activity::{Activity, ActivityData, ActivityOptions, FullActivityOptions},
+        announcements::Announcements,
+        attendance::{Attendance, AttendanceOptions},
+        behaviour::{Behaviour, BehaviourOptions},
+        detentions::Detentions,
+        homework::{HomeworkOptions, Homeworks},
+        lessons::Lessons,
+        pupils::{Pupil, PupilsData},
+        timetable::Timetable,
+    },
+    builder::ClientBuilder,
+    client::{Client, ClientCreationError, ErrorResponse, SessionState, PARENT_API_PATH},
+};
+
+/// A ClassCharts Parent Client.
+///
+/// Parents log in with their email and password, and can have multiple pupils linked to their
+/// account. Every request is made on behalf of the currently selected pupil, which defaults to the
+/// first pupil ClassCharts returns. Use `select_pupil` to switch between them.
+#[derive(Debug, Clone)]
+pub struct ParentClient {
+    pub pupils: PupilsData,
+    client: Client,
+}
+
+impl ParentClient {
+    /// This creates a ClassCharts Parent Client. It accepts an `email`, `password` and an optional
+    /// `base_url`, which should **rarely** be used and is only implimented for testing.
+    /// This is a shortcut for `ClientBuilder::new().login_parent(email, password)`.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// use classcharts::ParentClient;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = ParentClient::create("your email", "your password", None).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn create<E, P>(
+        email: E,
+        password: P,
+        base_url: Option<String>,
+    ) -> Result<Self, ClientCreationError>
+    where
+        E: ToString,
+        P: ToString,
+    {
+        let mut builder = ClientBuilder::new();
+
+        if let Some(base_url) = base_url {
+            builder = builder.base_url(base_url);
+        }
+
+        return builder.login_parent(email, password).await;
+    }
+
+    /// Exports the current session. See `Client::export_session`.
+    pub fn export_session(&self) -> SessionState {
+        return self.client.export_session();
+    }
+
+    /// Restores a parent client from a `SessionState` created by `export_session`, keeping the
+    /// selected pupil. See `Client::from_session`.
+    ///
+    /// Returns a `NotParentSessionError` if the session is for a student, and an
+    /// `UnknownPupilError` if the selected pupil is no longer linked to the account.
+    pub async fn from_session(state: SessionState) -> Result<Self, ClientCreationError> {
+        if state.api_path != PARENT_API_PATH {
+            return Err(ClientCreationError::NotParentSessionError);
+        }
+
+        let client = Client::from_session(state).await?;
+        let mut parent_client = ParentClient::from_client(client, vec![]);
+
+        let pupils = parent_client
+            .get_pupils()
+            .await
+            .map_err(ClientCreationError::ApiRequestError)?;
+
+        if pupils.data.is_empty() {
+            return Err(ClientCreationError::NoPupilsError);
+        }
+
+        let student_id = &parent_client.client.student_id;
+
+        if !pupils.data.iter().any(|pupil| pupil.id.to_string() == *student_id) {
+            return Err(ClientCreationError::UnknownPupilError(student_id.clone()));
+        }
+        parent_client.pupils = pupils.data;
+
+        return Ok(parent_client);
+    }
+
+    pub(crate) fn from_client(client: Client, pupils: PupilsData) -> ParentClient {
+        return ParentClient { pupils, client };
+    }
+
+    /// Returns the underlying client, which makes requests against the parent API.
+    pub fn client(&self) -> &Client {
+        return &self.client;
+    }
+
+    /// Selects which of the linked pupils future requests are made for.
+    /// The `pupil_id` must be one of the ids in `pupils`.
+    pub fn select_pupil(&mut self, pupil_id: usize) -> Result<(), ErrorResponse> {
+        if !self.pupils.iter().any(|pupil| pupil.id == pupil_id) {
+            return Err(ErrorResponse::UnknownPupilError(pupil_id));
+        }
+
+        self.client.student_id = pupil_id.to_string();
+
+        return Ok(());
+    }
+
+    /// Gets the currently selected pupil.
+    pub fn selected_pupil(&self) -> Option<&Pupil> {
+        return self
+            .pupils
+            .iter()
+            .find(|pupil| pupil.id.to_string() == self.client.student_id);
+    }
+
+    /// Gets the selected pupil's homework. See `Client::get_homeworks`.
+    pub async fn get_homeworks(
+        &self,
+        options: Option<HomeworkOptions>,
+    ) -> Result<Homeworks, ErrorResponse> {
+        return self.client.get_homeworks(options).await;
+    }
+
+    /// Gets the selected pupil's behaviour. See `Client::get_behaviour`.
+    pub async fn get_behaviour(
+        &self,
+        options: Option<BehaviourOptions>,
+    ) -> Result<Behaviour, ErrorResponse> {
+        return self.client.get_behaviour(options).await;
+    }
+
+    /// Gets the selected pupil's activity. See `Client::get_activity`.
+    pub async fn get_activity(
+        &self,
+        options: Option<ActivityOptions>,
+    ) -> Result<Activity, ErrorResponse> {
+        return self.client.get_activity(options).await;
+    }
+
+    /// Gets all of the selected pupil's activity between two dates. See
+    /// `Client::get_full_activity`.
+    pub async fn get_full_activity(
+        &self,
+        options: FullActivityOptions,
+    ) -> Result<ActivityData, ErrorResponse> {
+        return self.client.get_full_activity(options).await;
+    }
+
+    /// Gets the selected pupil's attendance. See `Client::get_attendance`.
+    pub async fn get_attendance(
+        &self,
+        options: Option<AttendanceOptions>,
+    ) -> Result<Attendance, ErrorResponse> {
+        return self.client.get_attendance(options).await;
+    }
+
+    /// Gets the selected pupil's detentions. See `Client::get_detentions`.
+    pub async fn get_detentions(&self) -> Result<Detentions, ErrorResponse> {
+        return self.client.get_detentions().await;
+    }
+
+    /// Gets the selected pupil's lessons for a given date. See `Client::get_lessons`.
+    pub async fn get_lessons(&self, date: NaiveDate) -> Result<Lessons, ErrorResponse> {
+        return self.client.get_lessons(date).await;
+    }
+
+    /// Gets the selected pupil's lessons between two dates. See `Client::get_timetable`.
+    pub async fn get_timetable(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Timetable, ErrorResponse> {
+        return self.client.get_timetable(from, to).await;
+    }
+
+    /// Gets the selected pupil's announcements. See `Client::get_announcements`.
+    pub async fn get_announcements(&self) -> Result<Announcements, ErrorResponse> {
+        return self.client.get_announcements().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn pupil_json(id: usize) -> serde_json::Value {
+        return json!({
+            "id": id,
+            "name": "Name",
+            "first_name": "first_name",
+            "last_name": "last_name",
+            "avatar_url": "https://example.com",
+            "school_name": "School Name",
+            "school_logo": "https://example.com",
+            "timezone": "Europe/London",
+            "display_behaviour": true,
+            "display_homework": true,
+            "display_rewards": false,
+            "display_detentions": true,
+            "display_announcements": true,
+            "display_attendance": true,
+            "display_timetable": true,
+            "announcements_count": 0,
+            "messages_count": 0,
+            "detention_yes_count": 1,
+            "detention_no_count": 0,
+            "detention_pending_count": 0,
+            "detention_upscaled_count": 0,
+            "homework_todo_count": 2,
+            "homework_late_count": 0,
+            "homework_not_completed_count": 0,
+            "homework_excused_count": 0,
+            "homework_completed_count": 5,
+            "homework_submitted_count": 0
+        });
+    }
+
+    #[tokio::test]
+    async fn create_parent_client_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let parent_login_response = server.mock(|when, then| {
+            when.method(POST).path("/parent/login");
+            then.status(302)
+                .header("content-type", "application/json")
+                .header(
+                    "set-cookie",
+                    "parent_session_credentials={\"session_id\":\"jf99rm23pdi29dj32fh23i\"}",
+                );
+        });
+
+        let pupils_response = server.mock(|when, then| {
+            when.method(GET).path("/apiv2parent/pupils");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [pupil_json(3949234)],
+                    "meta": []
+                }));
+        });
+
+        let mut client = ParentClient::create("my_email", "my_password", Some(server.base_url()))
+            .await
+            .unwrap();
+
+        assert_eq!(client.client().student_id, "3949234");
+        assert_eq!(client.client().session_id(), "jf99rm23pdi29dj32fh23i");
+        assert_eq!(client.selected_pupil().unwrap().first_name, "first_name");
+
+        client.select_pupil(3949234).unwrap();
+        client.select_pupil(1).unwrap_err();
+
+        parent_login_response.assert();
+        pupils_response.assert();
+    }
+
+    #[tokio::test]
+    async fn parent_from_session_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let ping_response = server.mock(|when, then| {
+            when.method(POST).path("/apiv2parent/ping");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": {},
+                    "meta": {
+                        "session_id": "new_session_id",
+                    },
+                }));
+        });
+
+        let pupils_response = server.mock(|when, then| {
+            when.method(GET).path("/apiv2parent/pupils");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [pupil_json(1), pupil_json(2)],
+                    "meta": []
+                }));
+        });
+
+        let mut state = ParentClient::generate_mock(server.base_url()).export_session();
+        state.student_id = "2".to_string();
+
+        let client = ParentClient::from_session(state.clone()).await.unwrap();
+
+        assert_eq!(client.selected_pupil().unwrap().id, 2);
+
+        state.student_id = "3".to_string();
+
+        let err = ParentClient::from_session(state).await.unwrap_err();
+
+        assert!(matches!(err, ClientCreationError::UnknownPupilError(id) if id == "3"));
+
+        let student_state = Client::generate_mock(server.base_url()).export_session();
+
+        let err = ParentClient::from_session(student_state).await.unwrap_err();
+
+        assert!(matches!(err, ClientCreationError::NotParentSessionError));
+
+        ping_response.assert_hits(2);
+        pupils_response.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn parent_endpoint_test() {
+        // Start a lightweight mock server.
+        let server = MockServer::start();
+
+        // Create a mock on the server.
+        let detentions_response = server.mock(|when, then| {
+            when.method(GET).path("/apiv2parent/detentions/student_id");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "success": 1,
+                    "data": [],
+                    "meta": {
+                        "detention_alias_plural": "detentions"
+                    }
+                }));
+        });
+
+        let client = ParentClient::generate_mock(server.base_url());
+
+        let _ = client.get_detentions().await.unwrap();
+
+        detentions_response.assert();
+    }
+}
@@ -0,0 +1,454 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method, StatusCode,
+};
+
+use crate::client::ErrorResponse;
+
+/// Controls when and how often failed requests are retried.
+///
+/// By default `GET` requests are attempted up to 3 times when the request fails to send or
+/// ClassCharts responds with `429` or a `5xx` status. `POST` requests are only retried when
+/// `retry_posts` is set, and `purchase_reward` is never retried.
+///
+/// Example:
+/// ```rust,no_run
+/// use classcharts::{ClientBuilder, RetryPolicy};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = ClientBuilder::new()
+///     .retry_policy(RetryPolicy {
+///         max_attempts: 5,
+///         initial_backoff: Duration::from_secs(1),
+///         ..RetryPolicy::default()
+///     })
+///     .login("your access code", "your date of birth (DD/MM/YYYY)")
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first. `1` disables retries.
+    pub max_attempts: u32,
+    /// The delay before the first retry, which doubles on every retry after that.
+    pub initial_backoff: Duration,
+    /// The longest delay between attempts, including delays requested with `Retry-After`.
+    pub max_backoff: Duration,
+    /// Randomises each delay between half and all of the backoff, so many clients don't retry at
+    /// the same time.
+    pub jitter: bool,
+    /// The response statuses which are retried.
+    pub retry_statuses: Vec<StatusCode>,
+    /// Whether requests which fail to send (timeouts, connection errors, etc.) are retried.
+    pub retry_transport_errors: bool,
+    /// Whether `POST` requests are retried as well as `GET` requests.
+    pub retry_posts: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        return RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_transport_errors: true,
+            retry_posts: false,
+        };
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries.
+    pub fn none() -> RetryPolicy {
+        return RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+    }
+
+    pub(crate) fn allows_method(&self, method: &Method) -> bool {
+        return *method == Method::GET || self.retry_posts;
+    }
+
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        return self.retry_statuses.contains(&status);
+    }
+
+    pub(crate) fn retries_error(&self, err: &ErrorResponse) -> bool {
+        if !self.retry_transport_errors {
+            return false;
+        }
+
+        return match err {
+            ErrorResponse::GenericClientError(err) => {
+                err.is_timeout() || err.is_connect() || err.is_request()
+            }
+            ErrorResponse::TransportError(_) => true,
+            _ => false,
+        };
+    }
+
+    /// The delay before the next attempt, after `attempt` attempts have failed.
+    pub(crate) fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_backoff);
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        let jitter = random_u64() % (half.as_millis() as u64 + 1);
+
+        return half + Duration::from_millis(jitter);
+    }
+}
+
+/// Reads a `Retry-After` header, which is either a number of seconds or an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+
+    return Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    );
+}
+
+/// A random number for jitter and multipart boundaries. It doesn't need to be secure.
+pub(crate) fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default(),
+    );
+
+    return hasher.finish();
+}
+
+/// A client side token bucket, which limits how many requests are sent to ClassCharts.
+///
+/// The bucket holds up to `burst` tokens and gains one every `per_request`. Every request takes a
+/// token, waiting for one if the bucket is empty.
+///
+/// Example:
+/// ```rust,no_run
+/// use classcharts::{ClientBuilder, RateLimit};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// // at most 2 requests a second, with bursts of up to 5
+/// let client = ClientBuilder::new()
+///     .rate_limit(RateLimit::new(5, Duration::from_millis(500)))
+///     .login("your access code", "your date of birth (DD/MM/YYYY)")
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_request: Duration,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, per_request: Duration) -> RateLimit {
+        return RateLimit { burst, per_request };
+    }
+
+    /// Allows `requests` requests a second, with bursts of the same size.
+    pub fn per_second(requests: u32) -> RateLimit {
+        return RateLimit::new(requests, Duration::from_secs(1) / requests.max(1));
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: tokio::sync::Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> RateLimiter {
+        return RateLimiter {
+            limit,
+            bucket: tokio::sync::Mutex::new(Bucket {
+                tokens: limit.burst as f64,
+                last_refill: Instant::now(),
+            }),
+        };
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        let burst = self.limit.burst.max(1) as f64;
+        let per_request = self.limit.per_request.as_secs_f64();
+
+        // holding the lock while sleeping keeps waiting requests in order
+        let mut bucket = self.bucket.lock().await;
+
+        loop {
+            let now = Instant::now();
+
+            if per_request > 0.0 {
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed / per_request).min(burst);
+            } else {
+                bucket.tokens = burst;
+            }
+            bucket.last_refill = now;
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+
+            let wait = (1.0 - bucket.tokens) * per_request;
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        transport::{Transport, TransportRequest, TransportResponse},
+        ClientBuilder,
+    };
+    use async_trait::async_trait;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Responds with each status in turn, then with the last one forever.
+    #[derive(Debug)]
+    struct SequenceTransport {
+        statuses: Mutex<Vec<StatusCode>>,
+        requests: Mutex<Vec<TransportRequest>>,
+    }
+
+    impl SequenceTransport {
+        fn new(mut statuses: Vec<StatusCode>) -> SequenceTransport {
+            statuses.reverse();
+
+            return SequenceTransport {
+                statuses: Mutex::new(statuses),
+                requests: Mutex::new(vec![]),
+            };
+        }
+
+        fn request_count(&self) -> usize {
+            return self.requests.lock().unwrap().len();
+        }
+    }
+
+    #[async_trait]
+    impl Transport for SequenceTransport {
+        async fn send(
+            &self,
+            request: TransportRequest,
+        ) -> Result<TransportResponse, ErrorResponse> {
+            let path = request.url.path().to_string();
+            self.requests.lock().unwrap().push(request);
+
+            let mut headers = HeaderMap::new();
+
+            if path == "/apiv2student/ping" {
+                return Ok(TransportResponse {
+                    status: StatusCode::OK,
+                    headers,
+                    body: json!({
+                        "success": 1,
+                        "data": {},
+                        "meta": { "session_id": "session_id" }
+                    })
+                    .to_string()
+                    .into_bytes(),
+                });
+            }
+
+            let status = {
+                let mut statuses = self.statuses.lock().unwrap();
+                if statuses.len() > 1 {
+                    statuses.pop().unwrap()
+                } else {
+                    statuses[0]
+                }
+            };
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                headers.insert(RETRY_AFTER, HeaderValue::from_static("0"));
+            }
+
+            let body = if status == StatusCode::OK {
+                json!({
+                    "success": 1,
+                    "data": { "note": "", "fields": [] },
+                    "meta": []
+                })
+                .to_string()
+            } else {
+                "Service Unavailable".to_string()
+            };
+
+            return Ok(TransportResponse {
+                status,
+                headers,
+                body: body.into_bytes(),
+            });
+        }
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        return RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+    }
+
+    async fn client(transport: Arc<SequenceTransport>, builder: ClientBuilder) -> crate::Client {
+        let state = crate::Client::generate_mock("http://localhost".to_string()).export_session();
+
+        return builder
+            .transport(transport)
+            .restore_session(state)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn backoff_test() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1, None), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2, None), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3, None), Duration::from_millis(350));
+        assert_eq!(
+            policy.backoff(1, Some(Duration::from_secs(10))),
+            Duration::from_millis(350)
+        );
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        }
+        .backoff(2, None);
+
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn retry_after_test() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn retries_get_test() {
+        let transport = Arc::new(SequenceTransport::new(vec![
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::OK,
+        ]));
+        let client = client(transport.clone(), ClientBuilder::new().retry_policy(fast_policy())).await;
+
+        client.get_pupilfields().await.unwrap();
+
+        // the ping from restoring the session, then three attempts
+        assert_eq!(transport.request_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_test() {
+        let transport = Arc::new(SequenceTransport::new(vec![StatusCode::BAD_GATEWAY]));
+        let client = client(transport.clone(), ClientBuilder::new().retry_policy(fast_policy())).await;
+
+        let err = client.get_pupilfields().await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            ErrorResponse::HttpStatusError(StatusCode::BAD_GATEWAY)
+        ));
+        assert_eq!(transport.request_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn purchase_is_never_retried_test() {
+        let transport = Arc::new(SequenceTransport::new(vec![StatusCode::SERVICE_UNAVAILABLE]));
+        let policy = RetryPolicy {
+            retry_posts: true,
+            ..fast_policy()
+        };
+        let client = client(transport.clone(), ClientBuilder::new().retry_policy(policy)).await;
+
+        client.purchase_reward(1).await.unwrap_err();
+
+        assert_eq!(transport.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_test() {
+        let limiter = RateLimiter::new(RateLimit::new(1, Duration::from_millis(50)));
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
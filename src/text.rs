@@ -0,0 +1,697 @@
+//! Converts the HTML ClassCharts uses for rich-text fields into plain text or Markdown.
+//!
+//! This is only available with the `text` feature. Tags are stripped, entities are decoded, and
+//! paragraphs, line breaks, lists and links are kept.
+//!
+//! ```rust
+//! use classcharts::text::{extract_links, html_to_markdown, html_to_text};
+//!
+//! let html = "<p>Read <a href=\"https://example.com\">this</a> &amp; answer:</p><ol><li>Q1</li><li>Q2</li></ol>";
+//!
+//! assert_eq!(html_to_text(html), "Read this (https://example.com) & answer:\n\n1. Q1\n2. Q2");
+//! assert_eq!(html_to_markdown(html), "Read [this](https://example.com) & answer:\n\n1. Q1\n2. Q2");
+//! assert_eq!(extract_links(html), vec!["https://example.com"]);
+//! ```
+
+use crate::api::{
+    announcements::Announcement, homework::Homework, lessons::Lesson,
+    pupilfields::PupilFieldsData,
+};
+
+/// Converts HTML to plain text. Links are written as `text (url)`.
+pub fn html_to_text(html: &str) -> String {
+    return Renderer::new(false).render(html);
+}
+
+/// Converts HTML to Markdown, keeping links, lists, headings and bold / italic text.
+pub fn html_to_markdown(html: &str) -> String {
+    return Renderer::new(true).render(html);
+}
+
+/// The urls of every link in the HTML, in order and without duplicates.
+pub fn extract_links(html: &str) -> Vec<String> {
+    let mut links: Vec<String> = vec![];
+
+    for token in tokenize(html) {
+        if let Token::Open { name, href, .. } = token {
+            if let Some(href) = href.filter(|_| name == "a") {
+                if is_link(&href) && !links.contains(&href) {
+                    links.push(href);
+                }
+            }
+        }
+    }
+
+    return links;
+}
+
+impl Homework {
+    /// The `description` as plain text.
+    pub fn description_text(&self) -> String {
+        return html_to_text(&self.description);
+    }
+
+    /// The `description` as Markdown.
+    pub fn description_markdown(&self) -> String {
+        return html_to_markdown(&self.description);
+    }
+
+    /// The links in the `description`. ClassCharts lists some of these in `validated_links`.
+    pub fn description_links(&self) -> Vec<String> {
+        return extract_links(&self.description);
+    }
+}
+
+impl Announcement {
+    /// The `description` as plain text.
+    pub fn description_text(&self) -> String {
+        return html_to_text(self.description.as_deref().unwrap_or_default());
+    }
+
+    /// The `description` as Markdown.
+    pub fn description_markdown(&self) -> String {
+        return html_to_markdown(self.description.as_deref().unwrap_or_default());
+    }
+
+    /// The links in the `description`.
+    pub fn description_links(&self) -> Vec<String> {
+        return extract_links(self.description.as_deref().unwrap_or_default());
+    }
+}
+
+impl Lesson {
+    /// The teacher's `note` as plain text.
+    pub fn note_text(&self) -> String {
+        return html_to_text(&self.note);
+    }
+
+    /// The teacher's `note` as Markdown.
+    pub fn note_markdown(&self) -> String {
+        return html_to_markdown(&self.note);
+    }
+
+    /// The student's `pupil_note` as plain text.
+    pub fn pupil_note_text(&self) -> String {
+        return html_to_text(&self.pupil_note);
+    }
+
+    /// The student's `pupil_note` as Markdown.
+    pub fn pupil_note_markdown(&self) -> String {
+        return html_to_markdown(&self.pupil_note);
+    }
+}
+
+impl PupilFieldsData {
+    /// The `note` as plain text.
+    pub fn note_text(&self) -> String {
+        return html_to_text(&self.note);
+    }
+
+    /// The `note` as Markdown.
+    pub fn note_markdown(&self) -> String {
+        return html_to_markdown(&self.note);
+    }
+}
+
+fn is_link(href: &str) -> bool {
+    let href = href.trim().to_lowercase();
+
+    return !href.is_empty() && !href.starts_with('#') && !href.starts_with("javascript:");
+}
+
+/// Escapes the characters Markdown would otherwise treat as formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        if matches!(char, '\\' | '*' | '_' | '[' | ']' | '`') {
+            escaped.push('\\');
+        }
+
+        escaped.push(char);
+    }
+
+    return escaped;
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open {
+        name: String,
+        href: Option<String>,
+        self_closing: bool,
+    },
+    Close(String),
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+
+        if start > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..start])));
+        }
+        rest = &rest[start..];
+
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+
+        let Some(end) = find_tag_end(rest) else {
+            // a lone `<` is text
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+
+        if let Some(token) = parse_tag(&rest[1..end]) {
+            tokens.push(token);
+        }
+        rest = &rest[end + 1..];
+    }
+
+    return tokens;
+}
+
+/// Finds the `>` which ends the tag at the start of `html`, skipping any in quoted attributes.
+fn find_tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, char) in html.char_indices().skip(1) {
+        match (quote, char) {
+            (None, '"' | '\'') => quote = Some(char),
+            (Some(open), _) if open == char => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+
+    return None;
+}
+
+fn parse_tag(tag: &str) -> Option<Token> {
+    let tag = tag.trim();
+
+    if let Some(name) = tag.strip_prefix('/') {
+        return Some(Token::Close(name.trim().to_lowercase()));
+    }
+
+    let self_closing = tag.ends_with('/');
+    let tag = tag.trim_end_matches('/');
+
+    let name_end = tag
+        .find(|char: char| char.is_whitespace())
+        .unwrap_or(tag.len());
+    let name = tag[..name_end].to_lowercase();
+
+    if name.is_empty() || !name.chars().all(|char| char.is_ascii_alphanumeric()) {
+        // doctypes, processing instructions, etc.
+        return None;
+    }
+
+    return Some(Token::Open {
+        href: attribute(&tag[name_end..], "href"),
+        name,
+        self_closing,
+    });
+}
+
+/// Finds an attribute's value. Attribute names are compared ASCII case-insensitively, and the
+/// attributes are only ever split on ASCII characters, so any other text is left intact.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+
+    loop {
+        rest = rest.trim_start_matches(|char: char| char.is_whitespace() || char == '/');
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        let name_end = rest
+            .find(|char: char| char.is_whitespace() || char == '=' || char == '/')
+            .unwrap_or(rest.len());
+        let attribute_name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+
+                match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = after[1..].find(quote).map_or(after.len(), |end| end + 1);
+                        rest = after.get(end + 1..).unwrap_or_default();
+                        &after[1..end]
+                    }
+                    _ => {
+                        let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                        rest = &after[end..];
+                        &after[..end]
+                    }
+                }
+            }
+            None => "",
+        };
+
+        if attribute_name.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end + 1]).map(|char| (char, end + 2)));
+
+        match entity {
+            Some((char, length)) => {
+                decoded.push(char);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+
+    return decoded;
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+
+        return char::from_u32(code);
+    }
+
+    let char = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "pound" => '£',
+        "euro" => '€',
+        "copy" => '©',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        _ => return None,
+    };
+
+    return Some(char);
+}
+
+struct List {
+    ordered: bool,
+    count: usize,
+}
+
+struct Renderer {
+    markdown: bool,
+    out: String,
+    lists: Vec<List>,
+    // the href of each open link, and its text so far
+    links: Vec<(Option<String>, String)>,
+    skip_depth: usize,
+    pre_depth: usize,
+}
+
+impl Renderer {
+    fn new(markdown: bool) -> Renderer {
+        return Renderer {
+            markdown,
+            out: String::new(),
+            lists: vec![],
+            links: vec![],
+            skip_depth: 0,
+            pre_depth: 0,
+        };
+    }
+
+    fn render(mut self, html: &str) -> String {
+        for token in tokenize(html) {
+            match token {
+                Token::Text(text) => self.text(&text),
+                Token::Open {
+                    name,
+                    href,
+                    self_closing,
+                } => {
+                    self.open(&name, href);
+
+                    if self_closing {
+                        self.close(&name);
+                    }
+                }
+                Token::Close(name) => self.close(&name),
+            }
+        }
+
+        return self.finish();
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip_depth > 0 {
+            return;
+        }
+
+        if self.pre_depth > 0 {
+            self.push_text(text);
+            return;
+        }
+
+        let mut words = text.split_whitespace().peekable();
+
+        if words.peek().is_none() {
+            if !text.is_empty() {
+                self.space();
+            }
+            return;
+        }
+
+        if text.starts_with(char::is_whitespace) {
+            self.space();
+        }
+
+        while let Some(word) = words.next() {
+            if self.markdown {
+                self.push_text(&escape_markdown(word));
+            } else {
+                self.push_text(word);
+            }
+
+            if words.peek().is_some() {
+                self.push_text(" ");
+            }
+        }
+
+        if text.ends_with(char::is_whitespace) {
+            self.space();
+        }
+    }
+
+    /// Adds text from the document, which is also the text of any open links.
+    fn push_text(&mut self, text: &str) {
+        self.out.push_str(text);
+
+        for (_, link_text) in &mut self.links {
+            link_text.push_str(text);
+        }
+    }
+
+    /// Adds a space, unless the output is at the start of a line or already ends with one.
+    fn space(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+            self.push_text(" ");
+        }
+    }
+
+    /// Ends the current line, so there are at least `count` line breaks before the next text.
+    fn line_breaks(&mut self, count: usize) {
+        while self.out.ends_with(' ') {
+            self.out.pop();
+        }
+
+        if self.out.is_empty() {
+            return;
+        }
+
+        let existing = self.out.chars().rev().take_while(|char| *char == '\n').count();
+
+        for _ in existing..count {
+            self.out.push('\n');
+        }
+    }
+
+    fn open(&mut self, name: &str, href: Option<String>) {
+        match name {
+            "script" | "style" | "head" | "title" => self.skip_depth += 1,
+            _ if self.skip_depth > 0 => {}
+            "br" => {
+                while self.out.ends_with(' ') {
+                    self.out.pop();
+                }
+                self.out.push('\n');
+            }
+            "p" | "div" | "table" | "blockquote" | "section" | "article" => self.line_breaks(2),
+            "tr" => self.line_breaks(1),
+            "td" | "th" => self.space(),
+            "hr" => {
+                self.line_breaks(2);
+                self.out.push_str("---");
+                self.line_breaks(2);
+            }
+            "pre" => {
+                self.line_breaks(2);
+                self.pre_depth += 1;
+
+                if self.markdown {
+                    self.out.push_str("```\n");
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.line_breaks(2);
+
+                if self.markdown {
+                    let level = name[1..].parse().unwrap_or(1);
+                    self.out.push_str(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "ul" | "ol" => {
+                self.line_breaks(if self.lists.is_empty() { 2 } else { 1 });
+                self.lists.push(List {
+                    ordered: name == "ol",
+                    count: 0,
+                });
+            }
+            "li" => {
+                self.line_breaks(1);
+
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(list) if list.ordered => {
+                        list.count += 1;
+                        format!("{}. ", list.count)
+                    }
+                    _ => "- ".to_string(),
+                };
+
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&marker);
+            }
+            "a" => {
+                let href = href.filter(|href| is_link(href));
+
+                if self.markdown && href.is_some() {
+                    self.out.push('[');
+                }
+
+                self.links.push((href, String::new()));
+            }
+            "strong" | "b" if self.markdown => self.out.push_str("**"),
+            "em" | "i" if self.markdown => self.out.push('_'),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "script" | "style" | "head" | "title" => {
+                self.skip_depth = self.skip_depth.saturating_sub(1)
+            }
+            _ if self.skip_depth > 0 => {}
+            "p" | "div" | "table" | "blockquote" | "section" | "article" | "h1" | "h2" | "h3"
+            | "h4" | "h5" | "h6" => self.line_breaks(2),
+            "tr" | "li" => self.line_breaks(1),
+            "pre" => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+
+                if self.markdown {
+                    self.line_breaks(1);
+                    self.out.push_str("```");
+                }
+
+                self.line_breaks(2);
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.line_breaks(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            "a" => {
+                let Some((Some(href), link_text)) = self.links.pop() else {
+                    return;
+                };
+
+                let trailing_space = self.out.ends_with(' ');
+
+                if trailing_space {
+                    self.out.pop();
+                }
+
+                if self.markdown {
+                    self.out.push_str(&format!("]({})", href));
+                } else if link_text.trim() != href {
+                    self.out.push_str(&format!(" ({})", href));
+                }
+
+                if trailing_space {
+                    self.out.push(' ');
+                }
+            }
+            "strong" | "b" if self.markdown => self.out.push_str("**"),
+            "em" | "i" if self.markdown => self.out.push('_'),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        let lines: Vec<&str> = self.out.lines().map(|line| line.trim_end()).collect();
+
+        let mut text = String::new();
+        let mut blank_lines = 0;
+
+        for line in lines {
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+
+            if !text.is_empty() {
+                text.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+            }
+
+            blank_lines = 0;
+            text.push_str(line);
+        }
+
+        return text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = "<p>Complete the worksheet&nbsp;on <strong>fractions</strong>.</p>\
+        <p>Useful links:</p>\
+        <ul>\
+            <li><a href=\"https://example.com/video\" target=\"_blank\">Video</a></li>\
+            <li><a href='https://example.com/notes?a=1&amp;b=2'>Notes &amp; answers</a></li>\
+        </ul>\
+        <p>Line one<br>Line two<br/>&lt;end&gt;</p>\
+        <script>alert('hi')</script>";
+
+    #[test]
+    fn html_to_text_test() {
+        assert_eq!(
+            html_to_text(DESCRIPTION),
+            "Complete the worksheet on fractions.\n\n\
+            Useful links:\n\n\
+            - Video (https://example.com/video)\n\
+            - Notes & answers (https://example.com/notes?a=1&b=2)\n\n\
+            Line one\nLine two\n<end>"
+        );
+    }
+
+    #[test]
+    fn html_to_markdown_test() {
+        assert_eq!(
+            html_to_markdown(DESCRIPTION),
+            "Complete the worksheet on **fractions**.\n\n\
+            Useful links:\n\n\
+            - [Video](https://example.com/video)\n\
+            - [Notes & answers](https://example.com/notes?a=1&b=2)\n\n\
+            Line one\nLine two\n<end>"
+        );
+    }
+
+    #[test]
+    fn markdown_escape_test() {
+        assert_eq!(
+            html_to_markdown("<p>2*3 = 6, snake_case, [1] and `code`</p><pre>a_b *c*</pre>"),
+            "2\\*3 = 6, snake\\_case, \\[1\\] and \\`code\\`\n\n```\na_b *c*\n```"
+        );
+        assert_eq!(html_to_text("<p>2*3 [1]</p>"), "2*3 [1]");
+    }
+
+    #[test]
+    fn attribute_test() {
+        assert_eq!(
+            extract_links("<a title=\"Ωéhref\" href=\"https://x.com\">x</a>"),
+            vec!["https://x.com"]
+        );
+        assert_eq!(
+            extract_links("<a title='İ K Ω' HREF='https://x.com'>x</a>"),
+            vec!["https://x.com"]
+        );
+        assert_eq!(
+            extract_links("<a data-href=\"https://y.com\" title=\"href=https://z.com\" href=https://x.com>x</a>"),
+            vec!["https://x.com"]
+        );
+        assert!(extract_links("<a title=\"Ω\" download>x</a>").is_empty());
+    }
+
+    #[test]
+    fn unbalanced_link_test() {
+        // these used to panic, as the output was trimmed after the link was opened
+        for html in [
+            "&amp;<p><td><pre> <a href='https://x'></pre></a>",
+            "<hr><pre>\n <a href='https://x'><p></a><pre>",
+            "x<pre> <a href='https://x'><a href='https://x'><h2></a><b>",
+        ] {
+            html_to_text(html);
+            html_to_markdown(html);
+        }
+    }
+
+    #[test]
+    fn nested_list_test() {
+        let html = "<ol><li>One<ul><li>A</li><li>B</li></ul></li><li>Two</li></ol>";
+
+        assert_eq!(html_to_text(html), "1. One\n  - A\n  - B\n2. Two");
+    }
+
+    #[test]
+    fn extract_links_test() {
+        assert_eq!(
+            extract_links(DESCRIPTION),
+            vec!["https://example.com/video", "https://example.com/notes?a=1&b=2"]
+        );
+        assert!(extract_links("<a href=\"javascript:void(0)\">x</a> a < b").is_empty());
+    }
+
+    #[test]
+    fn decode_entities_test() {
+        assert_eq!(decode_entities("&pound;5 &#8211; &#x41; &unknown; AT&T"), "£5 – A &unknown; AT&T");
+    }
+}
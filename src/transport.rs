@@ -0,0 +1,367 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode,
+};
+use url::Url;
+
+use crate::{
+    client::{cc_parse_text, CCParser, ErrorResponse},
+    retry::RateLimiter,
+};
+
+/// A request which is ready to be sent by a `Transport`.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+}
+
+/// The response a `Transport` returns. The whole body is read before it is returned.
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Sends HTTP requests for a `Client`.
+///
+/// The default transport is `ReqwestTransport`. Implementing this yourself lets you use an
+/// in-memory fake in tests, wrap requests in middleware, or use a different HTTP client.
+/// Transports **must not** follow redirects, as ClassCharts responds to a successful login with a
+/// redirect which sets the session cookies.
+///
+/// Example:
+/// ```rust,no_run
+/// use classcharts::{ClientBuilder, ErrorResponse, Transport, TransportRequest, TransportResponse};
+/// use std::sync::Arc;
+///
+/// #[derive(Debug)]
+/// struct LoggingTransport<T>(T);
+///
+/// #[async_trait::async_trait]
+/// impl<T: Transport> Transport for LoggingTransport<T> {
+///     async fn send(&self, request: TransportRequest) -> Result<TransportResponse, ErrorResponse> {
+///         println!("{} {}", request.method, request.url);
+///         return self.0.send(request).await;
+///     }
+/// }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = ClientBuilder::new()
+///     .transport(Arc::new(LoggingTransport(classcharts::ReqwestTransport::default())))
+///     .login("your access code", "your date of birth (DD/MM/YYYY)")
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[async_trait]
+pub trait Transport: Send + Sync + fmt::Debug {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, ErrorResponse>;
+}
+
+/// The default `Transport`, which sends requests with `reqwest`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Uses an existing `reqwest::Client`, which **must not** follow redirects.
+    pub fn new(client: reqwest::Client) -> ReqwestTransport {
+        return ReqwestTransport { client };
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> ReqwestTransport {
+        return ReqwestTransport::new(
+            reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap(),
+        );
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, ErrorResponse> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(ErrorResponse::TextParsingError)?
+            .to_vec();
+
+        return Ok(TransportResponse {
+            status,
+            headers,
+            body,
+        });
+    }
+}
+
+/// Builds a request which is sent through the client's `Transport`.
+/// Created by `Client::build_get` and `Client::build_post`.
+pub struct RequestBuilder {
+    transport: Arc<dyn Transport>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request: Result<TransportRequest, ErrorResponse>,
+}
+
+impl fmt::Debug for RequestBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f
+            .debug_struct("RequestBuilder")
+            .field("request", &self.request)
+            .finish();
+    }
+}
+
+impl RequestBuilder {
+    pub(crate) fn new(transport: Arc<dyn Transport>, method: Method, url: &str) -> RequestBuilder {
+        let request = Url::parse(url)
+            .map(|url| TransportRequest {
+                method,
+                url,
+                headers: HeaderMap::new(),
+                body: None,
+            })
+            .map_err(|err| ErrorResponse::InvalidRequestError(err.to_string()));
+
+        return RequestBuilder {
+            transport,
+            rate_limiter: None,
+            request,
+        };
+    }
+
+    /// Waits for the client's rate limiter before the request is sent.
+    pub(crate) fn rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> RequestBuilder {
+        self.rate_limiter = rate_limiter;
+        return self;
+    }
+
+    /// Adds a header to the request.
+    pub fn header<K, V>(mut self, key: K, value: V) -> RequestBuilder
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: fmt::Display,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: fmt::Display,
+    {
+        if let Ok(request) = &mut self.request {
+            let header = HeaderName::try_from(key)
+                .map_err(|err| err.to_string())
+                .and_then(|key| {
+                    HeaderValue::try_from(value)
+                        .map(|value| (key, value))
+                        .map_err(|err| err.to_string())
+                });
+
+            match header {
+                Ok((key, value)) => {
+                    request.headers.insert(key, value);
+                }
+                Err(err) => self.request = Err(ErrorResponse::InvalidRequestError(err)),
+            }
+        }
+
+        return self;
+    }
+
+    /// Sets the body of the request.
+    pub fn body<B>(mut self, body: B) -> RequestBuilder
+    where
+        B: Into<Vec<u8>>,
+    {
+        if let Ok(request) = &mut self.request {
+            request.body = Some(body.into());
+        }
+
+        return self;
+    }
+
+    /// Returns the request without sending it.
+    pub fn build(self) -> Result<TransportRequest, ErrorResponse> {
+        return self.request;
+    }
+
+    /// Sends the request through the client's `Transport`, waiting for the client's rate limit
+    /// first if one is set.
+    pub async fn send(self) -> Result<TransportResponse, ErrorResponse> {
+        let request = self.request?;
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        return self.transport.send(request).await;
+    }
+}
+
+#[async_trait]
+impl CCParser for TransportResponse {
+    /// Parses a `TransportResponse` in the same way as a `reqwest::Response`.
+    async fn cc_parse(self) -> Result<String, ErrorResponse> {
+        let text = String::from_utf8(self.body)?;
+
+        return cc_parse_text(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientBuilder;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct FakeTransport {
+        requests: Mutex<Vec<TransportRequest>>,
+    }
+
+    #[async_trait]
+    impl Transport for FakeTransport {
+        async fn send(
+            &self,
+            request: TransportRequest,
+        ) -> Result<TransportResponse, ErrorResponse> {
+            let path = request.url.path().to_string();
+            self.requests.lock().unwrap().push(request);
+
+            let mut headers = HeaderMap::new();
+
+            let (status, body) = match path.as_str() {
+                "/student/login" => {
+                    headers.insert(
+                        "set-cookie",
+                        HeaderValue::from_static(
+                            "student_session_credentials={\"session_id\":\"fake_session_id\"}",
+                        ),
+                    );
+                    (StatusCode::FOUND, json!({}))
+                }
+                "/apiv2student/ping" => (
+                    StatusCode::OK,
+                    json!({
+                        "success": 1,
+                        "data": {
+                            "user": {
+                                "id": 3949234,
+                                "name": "Name",
+                                "first_name": "first_name",
+                                "last_name": "last_name",
+                                "avatar_url": "https://example.com",
+                                "display_behaviour": false,
+                                "display_parent_behaviour": false,
+                                "display_homework": false,
+                                "display_rewards": false,
+                                "display_detentions": false,
+                                "display_report_cards": false,
+                                "display_classes": false,
+                                "display_announcements": true,
+                                "display_attendance": true,
+                                "display_attendance_type": "instance",
+                                "display_attendance_percentage": false,
+                                "display_activity": false,
+                                "display_mental_health": false,
+                                "display_timetable": false,
+                                "is_disabled": false,
+                                "display_two_way_communications": true,
+                                "display_absences": false,
+                                "can_upload_attachments": false,
+                                "display_event_badges": false,
+                                "display_avatars": false,
+                                "display_concern_submission": false,
+                                "display_custom_fields": false,
+                                "pupil_concerns_help_text": "",
+                                "allow_pupils_add_timetable_notes": false,
+                                "detention_alias_plural_uc": "Detentions",
+                                "announcements_count": 0,
+                                "messages_count": 0,
+                                "pusher_channel_name": "pusher_channel_name",
+                                "has_birthday": false,
+                                "has_new_survey": false,
+                                "survey_id": null
+                            }
+                        },
+                        "meta": {
+                            "session_id": "fake_session_id",
+                            "version": "27.16.2",
+                        },
+                    }),
+                ),
+                "/apiv2student/customfields/3949234" => (
+                    StatusCode::OK,
+                    json!({
+                        "success": 1,
+                        "data": {
+                            "note": "Note",
+                            "fields": []
+                        },
+                        "meta": [],
+                    }),
+                ),
+                _ => (StatusCode::NOT_FOUND, json!({})),
+            };
+
+            return Ok(TransportResponse {
+                status,
+                headers,
+                body: body.to_string().into_bytes(),
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn fake_transport_test() {
+        let transport = Arc::new(FakeTransport::default());
+
+        let client = ClientBuilder::new()
+            .transport(transport.clone())
+            .login("my_code", "my_dob")
+            .await
+            .unwrap();
+
+        let pupilfields = client.get_pupilfields().await.unwrap();
+
+        assert_eq!(client.student_id, "3949234");
+        assert_eq!(pupilfields.data.note, "Note");
+
+        let requests = transport.requests.lock().unwrap();
+        let paths: Vec<&str> = requests.iter().map(|request| request.url.path()).collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                "/student/login",
+                "/apiv2student/ping",
+                "/apiv2student/customfields/3949234"
+            ]
+        );
+        assert_eq!(
+            requests[2].headers.get("Authorization").unwrap(),
+            "Basic fake_session_id"
+        );
+    }
+}